@@ -0,0 +1,103 @@
+//! Encryption with the `age` command line tool (or `rage`, which takes the same arguments) for
+//! notes files and private annotations.
+//!
+//! ```toml
+//! [age]
+//! recipient = "age1..."
+//! identity = "~/.config/age/key.txt"
+//! notes = true          # keep notes as <uuid>.md.age
+//! annotations = true    # encrypt private annotations when adding them
+//! ```
+//!
+//! An encrypted annotation keeps the private prefix followed by `age:` and the ASCII-armored
+//! ciphertext without its header, footer and line breaks, since annotations are a single line.
+
+use crate::{config::AgeConfig, paths};
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+pub const ANNOTATION_MARKER: &str = "age:";
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const ARMOR_END: &str = "-----END AGE ENCRYPTED FILE-----";
+const ARMOR_WIDTH: usize = 64;
+
+static CONFIG: OnceLock<AgeConfig> = OnceLock::new();
+
+pub fn init(config: &AgeConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+pub fn config() -> Option<&'static AgeConfig> {
+    CONFIG.get().filter(|config| config.recipient.is_some())
+}
+
+pub fn encrypt(plaintext: &[u8], armor: bool) -> Result<Vec<u8>> {
+    let config = config().ok_or_else(|| anyhow!(tr!("Set [age] recipient to encrypt")))?;
+    let recipient = config.recipient.as_deref().expect("checked by config()");
+    let mut args = vec!["-r", recipient];
+    if armor {
+        args.push("-a");
+    }
+    run(&config.program, &args, plaintext)
+}
+
+pub fn decrypt(ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let config = config().ok_or_else(|| anyhow!(tr!("Set [age] recipient to encrypt")))?;
+    let identity = config
+        .identity
+        .as_deref()
+        .ok_or_else(|| anyhow!(tr!("Set [age] identity to decrypt")))?;
+    let identity = paths::expand_home(identity)?.display().to_string();
+    run(&config.program, &["-d", "-i", &identity], ciphertext)
+}
+
+/// Encrypts text into a single line for an annotation.
+pub fn encrypt_line(text: &str) -> Result<String> {
+    let armored = String::from_utf8(encrypt(text.as_bytes(), true)?)?;
+    Ok(armored
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect())
+}
+
+pub fn decrypt_line(line: &str) -> Result<String> {
+    let mut armored = vec![ARMOR_BEGIN.to_string()];
+    let chars: Vec<_> = line.trim().chars().collect();
+    armored.extend(
+        chars
+            .chunks(ARMOR_WIDTH)
+            .map(|chunk| chunk.iter().collect::<String>()),
+    );
+    armored.push(ARMOR_END.to_string());
+    Ok(String::from_utf8(decrypt(
+        (armored.join("\n") + "\n").as_bytes(),
+    )?)?)
+}
+
+fn run(program: &str, args: &[&str], input: &[u8]) -> Result<Vec<u8>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| tr!("running {}", program))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(tr!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
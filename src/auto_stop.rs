@@ -0,0 +1,133 @@
+//! Catches timers left running by accident: once enabled, every task active for longer than
+//! `[auto_stop] after` (default `8h`) is offered on startup for stopping, fixing its start, or
+//! logging the time actually worked to timewarrior (in builds with the `timewarrior` feature).
+//!
+//! ```toml
+//! [auto_stop]
+//! enabled = true
+//! after = "8h"
+//! ```
+
+use crate::{
+    back_on_escape, duration, task_command,
+    widgets::{self, rich_rofi, Row},
+    App,
+};
+use anyhow::{Context, Result};
+use chrono::{offset::Local as LocalTime, DateTime, TimeZone};
+use task_hookrs::task::Task;
+
+enum Choice {
+    Stop,
+    AdjustStart,
+    #[cfg(feature = "timewarrior")]
+    LogToTimewarrior,
+    Keep,
+}
+
+/// `pending` is the startup query, shared with the other startup checks. Returns whether any task
+/// was offered, since that may have changed it.
+pub fn check(app: &App, pending: &[Task]) -> Result<bool> {
+    let config = &app.config.auto_stop;
+    if !config.enabled {
+        return Ok(false);
+    }
+    // A mistake in the config is shown, but doesn't keep the menu from opening
+    let limit = match duration::parse(&config.after) {
+        Ok(limit) => limit,
+        Err(err) => {
+            let message = tr!("auto_stop.after: {}", err);
+            back_on_escape(widgets::message(&message).map_err(Into::into))?;
+            return Ok(false);
+        }
+    };
+    let now = LocalTime::now();
+
+    let mut offered = false;
+    for task in pending {
+        let start = match task.start() {
+            Some(start) => LocalTime.from_utc_datetime(start),
+            None => continue,
+        };
+        if now - start > limit {
+            // Escape leaves this task running and moves on
+            back_on_escape(offer(task, start))?;
+            offered = true;
+        }
+    }
+    Ok(offered)
+}
+
+fn offer(task: &Task, start: DateTime<LocalTime>) -> Result<()> {
+    let elapsed = chrono::Duration::minutes((LocalTime::now() - start).num_minutes());
+    let prompt = tr!(
+        "Running for {}: {}",
+        duration::format(elapsed),
+        task.description()
+    );
+    let mut choices = vec![Row::new(tr!("Stop"), Choice::Stop)];
+    #[cfg(feature = "timewarrior")]
+    choices.push(Row::new(
+        tr!("Stop and log the time worked to timewarrior"),
+        Choice::LogToTimewarrior,
+    ));
+    choices.push(Row::new(
+        tr!("Fix the start time, keep running"),
+        Choice::AdjustStart,
+    ));
+    choices.push(Row::new(tr!("Keep running"), Choice::Keep));
+    let uuid = task.uuid().to_string();
+
+    match rich_rofi(&prompt, choices)? {
+        Choice::Stop => stop(&uuid),
+        Choice::AdjustStart => {
+            let ago = duration::pick_duration(&tr!("Started how long ago?"), &["30m", "1h", "2h"])?;
+            let start = LocalTime::now() - ago;
+            task_command(vec![
+                &uuid,
+                "mod",
+                &format!("start:{}", start.format("%Y-%m-%dT%H:%M:%S")),
+            ])
+            .context(tr!("modifying start"))?;
+            Ok(())
+        }
+        #[cfg(feature = "timewarrior")]
+        Choice::LogToTimewarrior => {
+            track(task, start)?;
+            stop(&uuid)
+        }
+        Choice::Keep => Ok(()),
+    }
+}
+
+/// Asks how long was worked and logs it from `start`, tagged like the task.
+#[cfg(feature = "timewarrior")]
+fn track(task: &Task, start: DateTime<LocalTime>) -> Result<()> {
+    let worked = duration::pick_duration(
+        &tr!("How long did you work on it?"),
+        &["30m", "1h", "2h", "4h"],
+    )?;
+    let mut args = vec![
+        "track".to_string(),
+        start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        "-".to_string(),
+        (start + worked).format("%Y-%m-%dT%H:%M:%S").to_string(),
+    ];
+    args.extend(task.tags().cloned().unwrap_or_default());
+    if args.len() == 4 {
+        args.push(task.description().to_string());
+    }
+    let status = std::process::Command::new("timew")
+        .args(&args)
+        .status()
+        .context(tr!("running timew, is timewarrior installed?"))?;
+    if !status.success() {
+        anyhow::bail!(tr!("timew track failed"));
+    }
+    Ok(())
+}
+
+fn stop(uuid: &str) -> Result<()> {
+    task_command(vec![uuid, "stop"]).context(tr!("stopping task"))?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Snapshots of tasks taken before bulk changes, in the `backups` directory next to the state
+//! file, and restoring one with `task import`.
+
+use crate::{
+    paths, probe, task_command,
+    widgets::{self, rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use chrono::offset::Local as LocalTime;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Exports the tasks about to be changed. `reason` ends up in the file name.
+pub fn snapshot(uuids: &[&str], reason: &str) -> Result<PathBuf> {
+    let mut args = uuids.to_vec();
+    args.extend(probe::capabilities().export_args());
+    let (json, _) = task_command(args).context(tr!("exporting tasks for a backup"))?;
+
+    let dir = paths::backups_dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.json",
+        LocalTime::now().format("%Y%m%d-%H%M%S"),
+        reason
+    ));
+    fs::write(&path, json).with_context(|| tr!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn restore() -> Result<()> {
+    let dir = paths::backups_dir()?;
+    let mut backups: Vec<_> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+            .collect(),
+        Err(_) => vec![],
+    };
+    if backups.is_empty() {
+        bail!(tr!("No backups in {}", dir.display()));
+    }
+    // Names start with the timestamp, so this puts the newest first
+    backups.sort();
+    backups.reverse();
+
+    let rows = backups
+        .into_iter()
+        .map(|path| Row::new(describe(&path), path))
+        .collect();
+    let path: PathBuf = rich_rofi(&tr!("Restore which backup?"), rows)?;
+
+    let question = tr!("Overwrite these tasks with {}?", describe(&path));
+    if !widgets::confirm(&question, &tr!("Restore"), &tr!("Cancel"), vec![])? {
+        return Ok(());
+    }
+    task_command(vec!["import", &path.to_string_lossy()]).context(tr!("importing backup"))?;
+    Ok(())
+}
+
+/// Like `20261014-093000-reschedule (5 tasks)`.
+fn describe(path: &Path) -> String {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let count = fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+        .map(|tasks| tasks.len());
+    match count {
+        Some(count) => tr!("{} ({} tasks)", name, count),
+        None => name,
+    }
+}
@@ -0,0 +1,182 @@
+//! Balance: how the last few days were shared between projects compared to how they should be,
+//! and which project is furthest behind.
+//!
+//! ```toml
+//! [balance]
+//! targets = { work = 3, home = 1, learning = 1 }
+//! timewarrior = true   # compare time tracked rather than tasks completed
+//! days = 7
+//! ```
+//!
+//! Subprojects count towards their top-level target. Timewarrior intervals are matched by tag,
+//! which is how taskwarrior's timewarrior hook records the project, and need a build with the
+//! `timewarrior` feature.
+
+use crate::{
+    back_on_escape, duration, export, query_sorted, quick,
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+#[cfg(feature = "timewarrior")]
+use {anyhow::Context, serde::Deserialize, std::process::Command};
+
+/// An interval from `timew export`.
+#[cfg(feature = "timewarrior")]
+#[derive(Deserialize)]
+struct Interval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+struct Share {
+    project: String,
+    /// Tasks completed or seconds tracked.
+    amount: f64,
+    actual: f64,
+    target: f64,
+}
+
+pub fn balance(app: &App) -> Result<()> {
+    let config = &app.config.balance;
+    if config.targets.is_empty() {
+        bail!(tr!("Set [balance] targets in the config to use Balance"));
+    }
+
+    let since = Utc::now().naive_utc() - Duration::days(config.days);
+    let amounts = if config.timewarrior {
+        tracked(since)?
+    } else {
+        completed(since)?
+    };
+    let total: f64 = config
+        .targets
+        .keys()
+        .map(|project| amount_for(project, &amounts))
+        .sum();
+    let total_target: f64 = config.targets.values().sum();
+
+    let mut shares: Vec<_> = config
+        .targets
+        .iter()
+        .map(|(project, target)| {
+            let amount = amount_for(project, &amounts);
+            Share {
+                project: project.clone(),
+                amount,
+                actual: if total > 0.0 { amount / total } else { 0.0 },
+                target: target / total_target,
+            }
+        })
+        .collect();
+    // Furthest behind its target first
+    shares.sort_by(|a, b| (b.target - b.actual).total_cmp(&(a.target - a.actual)));
+
+    let width = shares
+        .iter()
+        .map(|share| share.project.len())
+        .max()
+        .unwrap_or(0);
+    let prompt = tr!("Balance: {} next", shares[0].project);
+    loop {
+        let rows = shares
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| {
+                let amount = if config.timewarrior {
+                    duration::format(Duration::minutes(share.amount as i64 / 60))
+                } else {
+                    tr!("{} done", share.amount as usize)
+                };
+                Row::new(
+                    format!(
+                        "{:<width$}  {:>3}% of {:>3}%  {}",
+                        share.project,
+                        (share.actual * 100.0).round(),
+                        (share.target * 100.0).round(),
+                        amount,
+                        width = width
+                    ),
+                    share.project.clone(),
+                )
+                .urgent(idx == 0 && share.actual < share.target)
+            })
+            .collect();
+
+        let project: String = rich_rofi(&prompt, rows)?;
+        let tasks = query_sorted(&format!("status:pending project:{}", project))?;
+        back_on_escape(quick::pick_and_act(&project, tasks, &app.config.style))?;
+    }
+}
+
+fn amount_for(project: &str, amounts: &[(String, f64)]) -> f64 {
+    amounts
+        .iter()
+        .filter(|(name, _)| in_project(name, project))
+        .map(|(_, amount)| amount)
+        .sum()
+}
+
+/// Whether `name` is `project` or one of its subprojects.
+fn in_project(name: &str, project: &str) -> bool {
+    name == project
+        || name
+            .strip_prefix(project)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Tasks completed since `since`, one per task with a project.
+fn completed(since: NaiveDateTime) -> Result<Vec<(String, f64)>> {
+    let filter = format!(
+        "status:completed end.after:{}",
+        since.format("%Y-%m-%dT%H:%M:%S")
+    );
+    Ok(export::query(&filter)?
+        .into_iter()
+        .filter_map(|task| Some((task.project()?.clone(), 1.0)))
+        .collect())
+}
+
+/// Seconds tracked in timewarrior since `since`, once per tag of each interval. Intervals still
+/// running count up to now.
+#[cfg(feature = "timewarrior")]
+fn tracked(since: NaiveDateTime) -> Result<Vec<(String, f64)>> {
+    let output = Command::new("timew")
+        .args([
+            "export",
+            &format!("{}Z", since.format("%Y-%m-%dT%H:%M:%S")),
+            "-",
+            "now",
+        ])
+        .output()
+        .context(tr!("running timew, is timewarrior installed?"))?;
+    if !output.status.success() {
+        bail!(tr!("timew export failed"));
+    }
+    let intervals: Vec<Interval> =
+        serde_json::from_slice(&output.stdout).context(tr!("parsing timew export"))?;
+
+    let parse = |time: &str| NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%SZ");
+    let now = Utc::now().naive_utc();
+    let mut amounts = vec![];
+    for interval in intervals {
+        let start = parse(&interval.start)?.max(since);
+        let end = match &interval.end {
+            Some(end) => parse(end)?,
+            None => now,
+        };
+        let seconds = (end - start).num_seconds().max(0) as f64;
+        amounts.extend(interval.tags.into_iter().map(|tag| (tag, seconds)));
+    }
+    Ok(amounts)
+}
+
+#[cfg(not(feature = "timewarrior"))]
+fn tracked(_since: NaiveDateTime) -> Result<Vec<(String, f64)>> {
+    bail!(tr!(
+        "This build has no timewarrior support; set [balance] timewarrior = false"
+    ))
+}
@@ -0,0 +1,99 @@
+//! A kanban-style board grouping the default report's tasks into columns by the `kanban` UDA.
+//!
+//! Columns come from `uda.kanban.values` in taskrc, falling back to backlog/doing/review/done.
+//! Tasks with no `kanban` value sit in the first column.
+
+use crate::{
+    default_tasks, format_task, get_config_var,
+    style::{Glyph, Style},
+    task_command,
+    widgets::{rich_rofi, Row},
+    App, TaskExt,
+};
+use anyhow::{Context, Result};
+use task_hookrs::task::Task;
+
+const UDA: &str = "kanban";
+const DEFAULT_COLUMNS: [&str; 4] = ["backlog", "doing", "review", "done"];
+
+pub fn board(app: &App) -> Result<()> {
+    let columns = columns();
+
+    loop {
+        let tasks = default_tasks(app)?;
+        let mut rows = vec![];
+        for (idx, column) in columns.iter().enumerate() {
+            rows.push(Row::header(
+                format!(
+                    "{rule} {} {rule}",
+                    column,
+                    rule = app.config.style.glyph(Glyph::Rule)
+                ),
+                None,
+            ));
+            rows.extend(
+                tasks
+                    .iter()
+                    .filter(|task| column_index(task, &columns) == idx)
+                    .map(|task| {
+                        Row::new(
+                            format!("    {}", format_task(task, &app.config.style)),
+                            Some(task.clone()),
+                        )
+                    }),
+            );
+        }
+
+        if let Some(task) = rich_rofi(&tr!("Board"), rows)? {
+            if !app.config.read_only {
+                move_task(&task, &columns, &app.config.style)?;
+            }
+        }
+    }
+}
+
+fn move_task(task: &Task, columns: &[String], style: &Style) -> Result<()> {
+    let current = column_index(task, columns);
+    let mut choices = vec![];
+    if let Some(next) = columns.get(current + 1) {
+        choices.push(Row::new(
+            tr!("Move to {} {}", next, style.glyph(Glyph::ArrowRight)),
+            Some(next),
+        ));
+    }
+    if let Some(previous) = current.checked_sub(1).and_then(|idx| columns.get(idx)) {
+        choices.push(Row::new(
+            tr!("{} Move to {}", style.glyph(Glyph::ArrowLeft), previous),
+            Some(previous),
+        ));
+    }
+    choices.push(Row::new(tr!("Back"), None));
+
+    if let Some(column) = rich_rofi(task.description(), choices)? {
+        task_command(vec![
+            &task.uuid().to_string(),
+            "mod",
+            &format!("{}:{}", UDA, column),
+        ])
+        .context(tr!("moving task to another column"))?;
+    }
+
+    Ok(())
+}
+
+fn columns() -> Vec<String> {
+    match get_config_var(&format!("uda.{}.values", UDA)) {
+        Ok(values) => values
+            .split(',')
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect(),
+        Err(_) => DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+fn column_index(task: &Task, columns: &[String]) -> usize {
+    task.uda_string(UDA)
+        .and_then(|value| columns.iter().position(|column| *column == value))
+        .unwrap_or(0)
+}
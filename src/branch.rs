@@ -0,0 +1,63 @@
+//! Git branch names derived from tasks.
+
+use crate::{
+    config::BranchConfig,
+    copy::to_clipboard,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use std::process::Command;
+use task_hookrs::task::Task;
+
+const MAX_SLUG: usize = 50;
+
+pub fn branch_for_task(task: &Task, config: &BranchConfig) -> Result<()> {
+    let branch = branch_name(task);
+
+    let mut choices = vec![Row::new(tr!("Copy {}", branch), None)];
+    if let Some(template) = config.command.as_ref().filter(|t| !t.trim().is_empty()) {
+        let args: Vec<_> = template
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{branch}", &branch)
+                    .replace("{repo}", &config.repo)
+            })
+            .collect();
+        choices.push(Row::new(tr!("Run {}", args.join(" ")), Some(args)));
+    }
+
+    match rich_rofi(&tr!("Branch"), choices)? {
+        None => to_clipboard(&branch),
+        Some(args) => {
+            let status = Command::new(&args[0])
+                .args(&args[1..])
+                .status()
+                .with_context(|| tr!("running {}", args[0]))?;
+            if !status.success() {
+                bail!(tr!("`{}` failed with {}", args.join(" "), status));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn branch_name(task: &Task) -> String {
+    let mut slug = String::new();
+    for c in task.description().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug: String = slug.chars().take(MAX_SLUG).collect();
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+
+    let suffix = match task.id() {
+        Some(id) if id > 0 => id.to_string(),
+        _ => task.uuid().to_string()[..8].to_string(),
+    };
+    format!("{}-{}", slug, suffix)
+}
@@ -0,0 +1,169 @@
+//! `warm`: a background process keeping the task list and taskwarrior's settings in a cache file,
+//! so a menu opened from a hotkey shows its list without waiting for `task export`.
+//!
+//! The warmer rebuilds the cache whenever the task data or taskrc changes, polling like
+//! `--watch`. It caches the default report's filter and each profile's. The menu uses the cache
+//! only when the data directory and taskrc haven't changed since it was written, and only when
+//! it was written by this version; anything else falls back to asking taskwarrior. Running
+//! without a warmer works as before.
+//!
+//! A systemd user unit for it:
+//!
+//! ```ini
+//! [Service]
+//! ExecStart=taskwarrior-rofi warm
+//! Restart=on-failure
+//!
+//! [Install]
+//! WantedBy=default.target
+//! ```
+
+use crate::{config::Config, dates, export, paths, task_command, watch};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+    time::{Duration, SystemTime},
+};
+use task_hookrs::task::Task;
+
+/// Bumped whenever the cache's layout changes, so older files are ignored.
+const FORMAT: u32 = 1;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    format: u32,
+    /// The version of this tool that wrote it.
+    version: String,
+    data_dir: PathBuf,
+    fingerprint: Fingerprint,
+    /// From `task _show`.
+    settings: BTreeMap<String, String>,
+    /// `export` output by filter.
+    exports: BTreeMap<String, Vec<Task>>,
+}
+
+/// What the cached data was read from, as modification times.
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Fingerprint {
+    data: Option<SystemTime>,
+    taskrc: Option<SystemTime>,
+}
+
+/// Runs until killed, rebuilding the cache after every change.
+pub fn warm() -> Result<()> {
+    let config = Config::load().context(tr!("loading config"))?;
+    let mut last = None;
+    loop {
+        // The data directory is looked up again in case taskrc moved it
+        let data_dir = paths::expand_home(&setting_from_task("data.location")?)?;
+        let current = fingerprint(&data_dir)?;
+        if last.as_ref() != Some(&current) {
+            let cache = build(&config, data_dir, current)?;
+            write(&cache)?;
+            last = Some(cache.fingerprint);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A cached taskwarrior setting, or `None` when the cache can't be used.
+pub fn setting(name: &str) -> Option<String> {
+    fresh().map(|cache| cache.settings.get(name).cloned().unwrap_or_default())
+}
+
+/// The cached export for `filter`, or `None` when it wasn't cached or the cache can't be used.
+pub fn tasks(filter: &str) -> Option<Vec<Task>> {
+    fresh()?.exports.get(filter).cloned()
+}
+
+/// The cache file's contents when they still match the task data.
+fn fresh() -> Option<&'static Cache> {
+    static CACHE: OnceLock<Option<Cache>> = OnceLock::new();
+    let cache = CACHE
+        .get_or_init(|| {
+            let contents = fs::read_to_string(cache_file().ok()?).ok()?;
+            serde_json::from_str::<Cache>(&contents).ok()
+        })
+        .as_ref()?;
+    if cache.format != FORMAT || cache.version != env!("CARGO_PKG_VERSION") {
+        return None;
+    }
+    // Checked on every use, since actions change the data while the menu is open
+    match fingerprint(&cache.data_dir) {
+        Ok(current) if current == cache.fingerprint => Some(cache),
+        _ => None,
+    }
+}
+
+fn build(config: &Config, data_dir: PathBuf, fingerprint: Fingerprint) -> Result<Cache> {
+    let (show, _) = task_command(vec!["_show"])?;
+    let settings: BTreeMap<_, _> = show
+        .lines()
+        .filter_map(dates::split_setting)
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    let mut filters: Vec<_> = config
+        .profiles
+        .iter()
+        .filter_map(|profile| profile.filter.clone())
+        .collect();
+    let report = settings
+        .get("default.command")
+        .and_then(|command| settings.get(&format!("report.{}.filter", command)));
+    if let Some(filter) = report {
+        filters.push(filter.clone());
+    }
+
+    let mut exports = BTreeMap::new();
+    for filter in filters {
+        let tasks = export::query(&filter)?;
+        exports.insert(filter, tasks);
+    }
+    Ok(Cache {
+        format: FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        data_dir,
+        fingerprint,
+        settings,
+        exports,
+    })
+}
+
+fn write(cache: &Cache) -> Result<()> {
+    let path = cache_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write then rename so a menu never reads a half-written cache
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(cache)?)?;
+    fs::rename(&tmp, &path).with_context(|| tr!("saving {}", path.display()))
+}
+
+fn fingerprint(data_dir: &Path) -> Result<Fingerprint> {
+    let taskrc = match env::var_os("TASKRC") {
+        Some(path) => PathBuf::from(path),
+        None => paths::home()?.join(".taskrc"),
+    };
+    Ok(Fingerprint {
+        data: watch::last_modified(data_dir)?,
+        taskrc: fs::metadata(taskrc).and_then(|meta| meta.modified()).ok(),
+    })
+}
+
+/// Like `get_config_var`, but always asking taskwarrior.
+fn setting_from_task(name: &str) -> Result<String> {
+    let (value, _) = task_command(vec!["_get", &format!("rc.{}", name)])?;
+    Ok(value.trim().to_string())
+}
+
+fn cache_file() -> Result<PathBuf> {
+    Ok(paths::cache_dir()?.join("warm.json"))
+}
@@ -0,0 +1,41 @@
+//! Calling the person a task is about, from a `phone` UDA or a `tel:` annotation.
+//!
+//! ```toml
+//! [dialer]
+//! command = "kdeconnect-cli --name phone --call {number}"
+//! ```
+//!
+//! Without a command the `tel:` link goes to the desktop's handler.
+
+use crate::{config::DialerConfig, run_template, widgets::rich_rofi, TaskExt};
+use anyhow::{bail, Context, Result};
+use task_hookrs::task::Task;
+
+pub fn call(task: &Task, config: &DialerConfig) -> Result<()> {
+    let mut numbers: Vec<String> = task.uda_string("phone").into_iter().collect();
+    let annotated = task
+        .annotations()
+        .into_iter()
+        .flatten()
+        .filter_map(|ann| ann.description().strip_prefix("tel:"));
+    for number in annotated {
+        let number = number.trim().to_string();
+        if !numbers.contains(&number) {
+            numbers.push(number);
+        }
+    }
+
+    let number = match numbers.len() {
+        0 => bail!(tr!("No phone number on this task")),
+        1 => numbers.swap_remove(0),
+        _ => rich_rofi(&tr!("Call which number?"), numbers)?,
+    };
+
+    match &config.command {
+        Some(command) => run_template(command, "{number}", &number),
+        None => {
+            open::that(format!("tel:{}", number)).context(tr!("Could not open the dialer"))?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Checklists kept as `[ ] step` / `[x] step` annotations on a single task.
+
+use crate::{
+    mutation,
+    widgets::{self, rich_rofi, Row},
+};
+use anyhow::{bail, Result};
+use task_hookrs::{annotation::Annotation, task::Task};
+
+const OPEN: &str = "[ ] ";
+const CHECKED: &str = "[x] ";
+
+enum Move {
+    Up,
+    Down,
+}
+
+/// Adds one unchecked annotation per line (or `;`-separated part) of the input.
+pub fn add_steps(task: &Task) -> Result<()> {
+    let input = widgets::text(&tr!("Checklist steps (; separated)"))?;
+    let steps: Vec<_> = input
+        .split(&['\n', ';'][..])
+        .map(str::trim)
+        .filter(|step| !step.is_empty())
+        .collect();
+    if steps.is_empty() {
+        bail!(tr!("No checklist steps given"));
+    }
+
+    // Taskwarrior moves an annotation added in the same second to the next free one, so the steps
+    // keep their order
+    let uuid = task.uuid().to_string();
+    for step in steps {
+        mutation::annotate(&uuid, &format!("{}{}", OPEN, step))?;
+    }
+    Ok(())
+}
+
+/// Flips a chosen checklist annotation between checked and unchecked. This edits the task in
+/// place rather than denotating and annotating, which would move the step to the end.
+pub fn toggle(task: &mut Task) -> Result<()> {
+    let items: Vec<_> = task
+        .annotations()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, ann)| is_checklist(ann))
+        .map(|(idx, ann)| Row::new(ann.description().to_string(), idx))
+        .collect();
+    if items.is_empty() {
+        bail!(tr!("This task has no checklist"));
+    }
+
+    let idx: usize = rich_rofi(&tr!("Toggle step"), items)?;
+    let annotations = task.annotations_mut().expect("checked above");
+    let description = annotations[idx].description_mut();
+    *description = if let Some(step) = description.strip_prefix(OPEN) {
+        format!("{}{}", CHECKED, step)
+    } else {
+        format!("{}{}", OPEN, &description[CHECKED.len()..])
+    };
+    Ok(())
+}
+
+/// Moves checklist steps up and down until the user is done. Annotations are kept in `entry`
+/// order, so a move swaps the timestamps of the two steps as well as their places.
+pub fn reorder(task: &mut Task) -> Result<()> {
+    loop {
+        let steps: Vec<usize> = task
+            .annotations()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, ann)| is_checklist(ann))
+            .map(|(idx, _)| idx)
+            .collect();
+        if steps.len() < 2 {
+            bail!(tr!("This task has nothing to reorder"));
+        }
+
+        let annotations = task.annotations_mut().expect("checked above");
+        let mut rows: Vec<_> = steps
+            .iter()
+            .enumerate()
+            .map(|(pos, idx)| Row::new(annotations[*idx].description().to_string(), Some(pos)))
+            .collect();
+        rows.push(Row::new(tr!("Done reordering"), None));
+        let pos = match rich_rofi(&tr!("Move step"), rows)? {
+            Some(pos) => pos,
+            None => return Ok(()),
+        };
+
+        let directions = vec![
+            Row::new(tr!("Move up"), Move::Up),
+            Row::new(tr!("Move down"), Move::Down),
+        ];
+        let other = match rich_rofi(annotations[steps[pos]].description(), directions)? {
+            Move::Up if pos > 0 => pos - 1,
+            Move::Down if pos + 1 < steps.len() => pos + 1,
+            // Already at the top or bottom
+            _ => continue,
+        };
+
+        let (a, b) = (steps[pos], steps[other]);
+        let entry = annotations[a].entry().clone();
+        *annotations[a].entry_mut() = annotations[b].entry().clone();
+        *annotations[b].entry_mut() = entry;
+        annotations.swap(a, b);
+    }
+}
+
+pub fn is_checklist(annotation: &Annotation) -> bool {
+    annotation.description().starts_with(OPEN) || annotation.description().starts_with(CHECKED)
+}
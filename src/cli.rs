@@ -0,0 +1,150 @@
+//! The command line, defined once with clap so parsing, shell completions and the man page stay
+//! in step. Without a subcommand the menu opens; the name of an action, like `done` or
+//! `project-status`, goes straight to that action and exits after it.
+
+use crate::Action;
+use anyhow::Result;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use clap_complete::Shell;
+use clap_mangen::Man;
+use std::sync::OnceLock;
+
+const NAME: &str = env!("CARGO_PKG_NAME");
+
+/// The options that apply wherever the menu opens, set once `main` has parsed the command line.
+static GLOBALS: OnceLock<Globals> = OnceLock::new();
+
+struct Globals {
+    read_only: bool,
+    filter: Option<String>,
+}
+
+pub fn command() -> Command {
+    let mut command = Command::new(NAME)
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("A rofi menu for taskwarrior")
+        .long_about(
+            "Without a subcommand, opens a menu of actions on taskwarrior tasks. Given the name \
+             of an action instead, in lowercase with dashes for spaces like `done` or \
+             `project-status`, goes straight to that action and exits after it.",
+        )
+        // Plugin and script actions are only known once the config is loaded
+        .allow_external_subcommands(true)
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .help("Reopen the menu whenever tasks change or a ping arrives"),
+        )
+        .arg(
+            Arg::new("read-only")
+                .long("read-only")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Open the menu with only the actions that change nothing"),
+        )
+        .arg(
+            Arg::new("filter")
+                .long("filter")
+                .global(true)
+                .value_name("FILTER")
+                .help("List the tasks matching a taskwarrior filter instead of the default report"),
+        );
+    #[cfg(feature = "notifications")]
+    {
+        command = command.subcommand(
+            Command::new("remind").about("Notify about old tasks, for running from a timer"),
+        );
+    }
+    #[cfg(feature = "http-api")]
+    {
+        command = command.subcommand(Command::new("serve").about("Run the local HTTP API"));
+    }
+    command = command
+        .subcommand(Command::new("review-old").about("Open the old tasks in the stalled view"))
+        .subcommand(
+            Command::new("ping")
+                .about("Make a running --watch reopen its menu, or send it a request")
+                .arg(
+                    Arg::new("request")
+                        .value_name("add <text> | done <uuid> | active")
+                        .num_args(0..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(Command::new("status").about("Print the focused task for a status bar"))
+        .subcommand(
+            Command::new("warm")
+                .about("Keep a cache of the task list fresh so the menu opens faster"),
+        )
+        .subcommand(
+            Command::new("menu")
+                .about("Pick from rows on stdin, dmenu style, for other scripts")
+                .disable_help_flag(true)
+                .arg(
+                    Arg::new("args")
+                        .value_name("-p <prompt> | -markup | -multi | -kb-custom-N <key>")
+                        .num_args(0..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(Command::new("doctor").about("Check the environment and suggest fixes"))
+        .subcommand(Command::new("init").about("Write a starting config file"))
+        .subcommand(
+            Command::new("completions")
+                .about("Print shell completions")
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .value_parser(value_parser!(Shell)),
+                ),
+        )
+        .subcommand(Command::new("manpage").about("Print the man page in roff format"))
+        .subcommand(
+            Command::new("theme")
+                .about("Print a rofi theme snippet matching how rows are styled")
+                .subcommand_required(true)
+                .subcommand(Command::new("export").about("Print the theme snippet")),
+        );
+    for (name, label) in Action::commands() {
+        command = command.subcommand(Command::new(name).about(label));
+    }
+    command
+}
+
+pub fn init(matches: &ArgMatches) {
+    let _ = GLOBALS.set(Globals {
+        read_only: matches.get_flag("read-only"),
+        filter: matches.get_one::<String>("filter").cloned(),
+    });
+}
+
+/// Whether `--read-only` was given.
+pub fn read_only() -> bool {
+    GLOBALS.get().is_some_and(|globals| globals.read_only)
+}
+
+/// The filter given with `--filter`.
+pub fn filter() -> Option<&'static str> {
+    GLOBALS.get()?.filter.as_deref()
+}
+
+pub fn completions(shell: Shell) -> String {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command(), NAME, &mut script);
+    String::from_utf8_lossy(&script).into_owned()
+}
+
+pub fn manpage() -> Result<String> {
+    let mut page = Vec::new();
+    Man::new(command()).render(&mut page)?;
+    // clap knows nothing of the files read outside the command line
+    page.extend_from_slice(
+        b".SH FILES\n\
+          .TP\n.I ~/.config/taskwarrior-rofi/config.toml\nSettings.\n\
+          .TP\n.I ~/.local/state/taskwarrior-rofi/state.json\nState kept between runs.\n",
+    );
+    Ok(String::from_utf8_lossy(&page).into_owned())
+}
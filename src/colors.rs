@@ -0,0 +1,131 @@
+//! Row colors from taskwarrior's own `color.project.*` and `color.tag.*` rules, so the menu
+//! matches the terminal without configuring colors twice. Only the foreground and `bold` are
+//! carried over; backgrounds would fight with rofi's selection highlight.
+
+use crate::{dates::split_setting, task_command};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use task_hookrs::task::Task;
+
+/// The eight basic colors, then their bright variants.
+const BASIC: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+const BASIC_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+pub struct Accent {
+    pub color: Option<String>,
+    pub bold: bool,
+}
+
+#[derive(Default)]
+pub struct TaskColors {
+    projects: BTreeMap<String, Accent>,
+    tags: BTreeMap<String, Accent>,
+}
+
+impl TaskColors {
+    pub fn load() -> Result<Self> {
+        let (settings, _) = task_command(vec!["_show"])?;
+        let mut colors = Self::default();
+        for (key, value) in settings.lines().filter_map(split_setting) {
+            let accent = match parse(value) {
+                Some(accent) => accent,
+                None => continue,
+            };
+            if let Some(project) = key.strip_prefix("color.project.") {
+                colors.projects.insert(project.to_string(), accent);
+            } else if let Some(tag) = key.strip_prefix("color.tag.") {
+                colors.tags.insert(tag.to_string(), accent);
+            }
+        }
+        Ok(colors)
+    }
+
+    /// The most specific project rule (`work.home` over `work`), else the first tag with one.
+    pub fn accent(&self, task: &Task) -> Option<&Accent> {
+        let project = task.project().and_then(|project| {
+            self.projects
+                .iter()
+                .filter(|(name, _)| project == *name || project.starts_with(&format!("{}.", name)))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, accent)| accent)
+        });
+        project.or_else(|| {
+            let mut tags: Vec<_> = task.tags().into_iter().flatten().collect();
+            tags.sort();
+            tags.into_iter().find_map(|tag| self.tags.get(tag))
+        })
+    }
+}
+
+/// Reads a taskwarrior color like `bold red on blue`, `color202`, `rgb450` or `gray12`.
+fn parse(value: &str) -> Option<Accent> {
+    let foreground = value.split(" on ").next()?;
+    let mut accent = Accent {
+        color: None,
+        bold: false,
+    };
+    let mut bright = false;
+    for word in foreground.split_whitespace() {
+        match word {
+            "bold" => accent.bold = true,
+            "bright" => bright = true,
+            word => accent.color = color(word, bright),
+        }
+    }
+    if accent.color.is_none() && !accent.bold {
+        return None;
+    }
+    Some(accent)
+}
+
+fn color(word: &str, bright: bool) -> Option<String> {
+    if let Some(index) = BASIC_NAMES.iter().position(|name| *name == word) {
+        return Some(BASIC[index + if bright { 8 } else { 0 }].to_string());
+    }
+    let index: u8 = if let Some(n) = word.strip_prefix("color") {
+        n.parse().ok()?
+    } else if let Some(n) = word
+        .strip_prefix("gray")
+        .or_else(|| word.strip_prefix("grey"))
+    {
+        232 + n.parse::<u8>().ok().filter(|n| *n < 24)?
+    } else if let Some(rgb) = word.strip_prefix("rgb") {
+        let digits: Vec<u8> = rgb
+            .chars()
+            .map(|c| c.to_digit(6).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        match digits.as_slice() {
+            [r, g, b] => 16 + 36 * r + 6 * g + b,
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(xterm_256(index))
+}
+
+/// The hex value of one of the 256 xterm colors.
+fn xterm_256(index: u8) -> String {
+    match index {
+        0..=15 => BASIC[index as usize].to_string(),
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(n / 36),
+                level(n / 6 % 6),
+                level(n % 6)
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            format!("#{:02x}{:02x}{:02x}", level, level, level)
+        }
+    }
+}
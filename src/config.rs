@@ -0,0 +1,490 @@
+//! User configuration, read from `~/.config/taskwarrior-rofi/config.toml`. Every setting is
+//! optional and a missing file means all defaults.
+
+use crate::{
+    paths,
+    schedule::{Profile, Window},
+    style::Style,
+};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs};
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub branch: BranchConfig,
+    pub profiles: Vec<Profile>,
+    /// Extra non-working days as `YYYY-MM-DD`, skipped by "next business day".
+    pub holidays: Vec<String>,
+    pub scheduler: SchedulerConfig,
+    pub sprint: SprintConfig,
+    pub stalled: StalledConfig,
+    pub archive: ArchiveConfig,
+    pub auto_stop: AutoStopConfig,
+    pub notes: NotesConfig,
+    pub vault: VaultConfig,
+    pub dialer: DialerConfig,
+    pub share: ShareConfig,
+    pub webhook: WebhookConfig,
+    pub quiet: QuietConfig,
+    pub privacy: PrivacyConfig,
+    pub age: AgeConfig,
+    pub session: SessionConfig,
+    pub sync: SyncConfig,
+    pub server: ServerConfig,
+    pub remind: RemindConfig,
+    pub balance: BalanceConfig,
+    pub someday: SomedayConfig,
+    pub add: AddConfig,
+    pub blocked: BlockedConfig,
+    pub inbox: InboxConfig,
+    pub menu: MenuConfig,
+    pub style: Style,
+    /// Only offer actions that change nothing, as with `--read-only`.
+    pub read_only: bool,
+    /// The first day of the week, like `sunday`. Defaults to what is usual for the locale's region.
+    pub week_start: Option<String>,
+    /// The program showing menus: `rofi`, `dmenu`, `wofi`, `fuzzel`, `bemenu`, `choose`, `fzf` or
+    /// `terminal`. Found on PATH when unset.
+    pub launcher: Option<String>,
+    /// Language for menu text, like `de_DE`. Defaults to the `LANG` family of variables.
+    pub locale: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct BranchConfig {
+    /// Command run by the Branch action, split on whitespace with `{branch}` and `{repo}`
+    /// substituted in each argument. When unset the branch name is only offered for copying.
+    pub command: Option<String>,
+    pub repo: String,
+}
+
+impl Default for BranchConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            repo: ".".to_string(),
+        }
+    }
+}
+
+/// Settings for the Plan action, in hours.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub daily_capacity: f64,
+    /// Used for tasks without an `estimate`.
+    pub default_estimate: f64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            daily_capacity: 6.0,
+            default_estimate: 1.0,
+        }
+    }
+}
+
+/// Sprint boundaries for `sprint+N` dates: sprints of `length` days, the first starting on
+/// `start` (`YYYY-MM-DD`).
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SprintConfig {
+    pub start: Option<String>,
+    pub length: i64,
+}
+
+impl Default for SprintConfig {
+    fn default() -> Self {
+        Self {
+            start: None,
+            length: 14,
+        }
+    }
+}
+
+/// Thresholds for the Stalled view.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StalledConfig {
+    pub days: i64,
+    pub min_urgency: f64,
+}
+
+impl Default for StalledConfig {
+    fn default() -> Self {
+        Self {
+            days: 14,
+            min_urgency: 5.0,
+        }
+    }
+}
+
+/// How long without activity makes a task old enough to suggest archiving.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub days: i64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { days: 90 }
+    }
+}
+
+/// When to offer stopping tasks that have been active for too long.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct AutoStopConfig {
+    pub enabled: bool,
+    /// A duration like `8h`.
+    pub after: String,
+}
+
+impl Default for AutoStopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after: "8h".to_string(),
+        }
+    }
+}
+
+/// Where the Notes action keeps its files and what opens them.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct NotesConfig {
+    /// A leading `~/` is the home directory.
+    pub dir: Option<String>,
+    /// Split on whitespace with `{file}` substituted in each argument.
+    pub command: Option<String>,
+}
+
+/// The "needs attention" screen before the main menu, off by default.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct InboxConfig {
+    pub enabled: bool,
+    pub limit: usize,
+}
+
+impl Default for InboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            limit: 10,
+        }
+    }
+}
+
+/// What the main menu offers and how task lists are put together.
+///
+/// ```toml
+/// [menu]
+/// actions = ["Add", "Done", "Start", "Stop", "Wait"]
+/// sort = "due"
+/// wait_presets = ["tomorrow", "monday", "1w"]
+///
+/// [menu.prompts]
+/// actions = "What now?"
+/// task = "Which one?"
+/// ```
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct MenuConfig {
+    /// Names of the actions to offer, in the menu's usual order. Empty offers them all.
+    pub actions: Vec<String>,
+    pub sort: SortOrder,
+    /// Replace the presets offered when picking a wait date.
+    pub wait_presets: Vec<String>,
+    pub prompts: PromptsConfig,
+}
+
+/// How task pickers order their rows. Ties always fall back to urgency.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Urgency,
+    /// Soonest due first, tasks without one last.
+    Due,
+    /// Oldest first.
+    Entry,
+    Description,
+}
+
+/// Replacements for the usual prompts.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct PromptsConfig {
+    /// The main menu's.
+    pub actions: Option<String>,
+    /// Picking the task an action works on.
+    pub task: Option<String>,
+    /// Add's text prompt.
+    pub add: Option<String>,
+}
+
+/// Blocked tasks are always dimmed and listed last; this also leaves them out when picking a task
+/// to start or finish.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct BlockedConfig {
+    pub hide_when_acting: bool,
+}
+
+/// Adding the same text again within `duplicate_window` seconds asks first, 0 turns it off.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct AddConfig {
+    pub duplicate_window: i64,
+    pub grammar: GrammarConfig,
+}
+
+impl Default for AddConfig {
+    fn default() -> Self {
+        Self {
+            duplicate_window: 5,
+            grammar: GrammarConfig::default(),
+        }
+    }
+}
+
+/// The quick-add sigils, see `grammar`. An empty sigil turns that shorthand off.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct GrammarConfig {
+    pub annotation: String,
+    pub project: String,
+    pub tag: String,
+    pub priority: String,
+    /// Words after the priority sigil, mapped to `H`, `M` or `L`.
+    pub priorities: BTreeMap<String, String>,
+}
+
+impl Default for GrammarConfig {
+    fn default() -> Self {
+        Self {
+            annotation: "--".to_string(),
+            project: String::new(),
+            tag: "+".to_string(),
+            priority: String::new(),
+            priorities: BTreeMap::new(),
+        }
+    }
+}
+
+/// How often the reviews offer a pass through the someday list, 0 for never.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SomedayConfig {
+    pub review_days: i64,
+}
+
+impl Default for SomedayConfig {
+    fn default() -> Self {
+        Self { review_days: 7 }
+    }
+}
+
+/// What the Call action runs, with `{number}` substituted.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct DialerConfig {
+    pub command: Option<String>,
+}
+
+/// The Share action's message, and the commands it can be sent to besides the clipboard.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ShareConfig {
+    pub template: String,
+    pub targets: Vec<ShareTarget>,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            template: "{description}\nProject: {project}\nDue: {due}\n{links}".to_string(),
+            targets: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ShareTarget {
+    pub name: String,
+    pub command: String,
+}
+
+/// Where changes are POSTed; nothing is sent without a `url`.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub url: Option<String>,
+    /// Seconds before a delivery counts as failed and is queued.
+    pub timeout: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout: 5,
+        }
+    }
+}
+
+/// When `remind` holds its notification back. With `drop` set it is skipped instead of waiting
+/// for the quiet hours to end.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct QuietConfig {
+    pub hours: Vec<Window>,
+    pub drop: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Annotations starting with this are private.
+    pub prefix: String,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            prefix: "private:".to_string(),
+        }
+    }
+}
+
+/// What gets encrypted with `age`; nothing does without a `recipient`.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AgeConfig {
+    pub program: String,
+    pub recipient: Option<String>,
+    /// A leading `~/` is the home directory.
+    pub identity: Option<String>,
+    pub notes: bool,
+    pub annotations: bool,
+}
+
+impl Default for AgeConfig {
+    fn default() -> Self {
+        Self {
+            program: "age".to_string(),
+            recipient: None,
+            identity: None,
+            notes: false,
+            annotations: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Show what was done on exit.
+    pub summary: bool,
+}
+
+/// Retries of the Sync action: up to `attempts` tries, waiting `backoff` seconds after the first
+/// failure and twice as long after each one after that.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub attempts: u32,
+    pub backoff: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 4,
+            backoff: 2,
+        }
+    }
+}
+
+/// The `serve` HTTP API, in builds with the `http-api` feature.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub listen: String,
+    pub token: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:8765".to_string(),
+            token: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct RemindConfig {
+    /// A notification per old task, each with snooze actions, instead of one digest.
+    pub per_task: bool,
+}
+
+/// Target shares for the Balance view, by top-level project. Shares are relative, so
+/// `{ work = 3, home = 1 }` means three quarters work.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    pub targets: BTreeMap<String, f64>,
+    /// Compare time tracked in timewarrior rather than completed tasks, in builds with the
+    /// `timewarrior` feature.
+    pub timewarrior: bool,
+    /// How far back to look.
+    pub days: i64,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            targets: BTreeMap::new(),
+            timewarrior: false,
+            days: 7,
+        }
+    }
+}
+
+/// An Obsidian vault that tasks link notes from, by wiki-name in the `uda` UDA.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    pub name: Option<String>,
+    pub path: Option<String>,
+    pub uda: String,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            path: None,
+            uda: "note".to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let path = paths::config_file()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents).with_context(|| tr!("parsing {}", path.display()))
+    }
+}
@@ -0,0 +1,127 @@
+//! Optimistic locking for changes made by saving a whole task: if taskwarrior's copy was modified
+//! after the task was picked, the user sees what changed and decides, instead of the stale copy
+//! silently overwriting someone else's edit.
+
+use crate::{
+    export, privacy, session, webhook,
+    widgets::{rich_rofi, Row},
+    MapFailure,
+};
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use task_hookrs::{task::Task, tw};
+
+/// Fields that change without anyone editing the task.
+const IGNORED: [&str; 3] = ["modified", "urgency", "id"];
+
+pub enum Outcome {
+    Saved,
+    RePick,
+    Aborted,
+}
+
+enum Choice {
+    Proceed,
+    RePick,
+    Abort,
+}
+
+/// Saves `changed`, an edited copy of `picked`. When taskwarrior's copy has moved on, "proceed"
+/// applies just the fields this edit touched on top of the current task. Picking the task again
+/// is only offered when `can_repick`, as the caller must then go back to its picker.
+pub fn save(picked: &Task, changed: &Task, can_repick: bool) -> Result<Outcome> {
+    let current = export::query(&picked.uuid().to_string())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!(tr!("{} no longer exists", picked.description())))?;
+    if current.modified() == picked.modified() {
+        tw::save(Some(changed)).map_failure()?;
+        webhook::saved(&[changed]);
+        session::saved(picked, changed);
+        return Ok(Outcome::Saved);
+    }
+
+    let picked_fields = fields(picked)?;
+    let current_fields = fields(&current)?;
+    let mut rows = vec![Row::new(tr!("Apply my change anyway"), Choice::Proceed)];
+    if can_repick {
+        rows.push(Row::new(tr!("Pick the task again"), Choice::RePick));
+    }
+    rows.push(Row::new(tr!("Abort"), Choice::Abort));
+    // Private annotations are masked in the preview; the real fields still decide what changed
+    let shown = (
+        fields(&privacy::masked(picked))?,
+        fields(&privacy::masked(&current))?,
+    );
+    rows.extend(
+        diff(&picked_fields, &current_fields, &shown)
+            .into_iter()
+            .map(|line| Row::header(format!("    {}", line), Choice::Abort)),
+    );
+
+    match rich_rofi(
+        &tr!("{} changed since you picked it", picked.description()),
+        rows,
+    )? {
+        Choice::Proceed => {
+            let mut merged = current_fields;
+            for (key, value) in diff_fields(&picked_fields, &fields(changed)?) {
+                match value {
+                    Some(value) => merged.insert(key, value),
+                    None => merged.remove(&key),
+                };
+            }
+            let merged: Task = serde_json::from_value(Value::Object(merged))?;
+            tw::save(Some(&merged)).map_failure()?;
+            webhook::saved(&[&merged]);
+            session::saved(picked, &merged);
+            Ok(Outcome::Saved)
+        }
+        Choice::RePick => Ok(Outcome::RePick),
+        Choice::Abort => Ok(Outcome::Aborted),
+    }
+}
+
+fn fields(task: &Task) -> Result<Map<String, Value>> {
+    match serde_json::to_value(task)? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(anyhow!("a task should serialize to an object")),
+    }
+}
+
+/// The fields that differ, with their new value (`None` when removed).
+fn diff_fields(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<(String, Option<Value>)> {
+    let mut keys: Vec<_> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| !IGNORED.contains(&key.as_str()))
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| (key.clone(), new.get(key).cloned()))
+        .collect()
+}
+
+/// One `field: old -> new` line per field changed from `old` to `new`, with the values taken from
+/// the `shown` copies of each.
+fn diff(
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    shown: &(Map<String, Value>, Map<String, Value>),
+) -> Vec<String> {
+    let show = |value: Option<&Value>| match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    };
+    diff_fields(old, new)
+        .into_iter()
+        .map(|(key, _)| {
+            format!(
+                "{}: {} -> {}",
+                key,
+                show(shown.0.get(&key)),
+                show(shown.1.get(&key))
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+//! Taskwarrior contexts, and the Context action that switches between them.
+
+use crate::{
+    dates, get_config_var, task_command,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+
+pub struct WriteContext {
+    pub name: String,
+    /// The modifications taskwarrior applies to every `add` while the context is active.
+    pub defaults: String,
+}
+
+/// Lists the contexts defined in taskrc, with the filter each one reads through, and makes the
+/// chosen one (or none) active as `task context` would.
+pub fn switch() -> Result<()> {
+    let (show, _) = task_command(vec!["_show"])?;
+    let settings: BTreeMap<_, _> = show.lines().filter_map(dates::split_setting).collect();
+    let mut contexts = BTreeMap::new();
+    for (key, value) in &settings {
+        let rest = match key.strip_prefix("context.") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        // `context.<name>` is how taskwarrior before 2.6 wrote `context.<name>.read`
+        if let Some(name) = rest
+            .strip_suffix(".read")
+            .or_else(|| (!rest.contains('.')).then_some(rest))
+        {
+            contexts.insert(name, *value);
+        } else if let Some(name) = rest.strip_suffix(".write") {
+            contexts.entry(name).or_insert("");
+        }
+    }
+    if contexts.is_empty() {
+        bail!(tr!("No contexts are defined"));
+    }
+
+    let (active, _) = task_command(vec!["_get", "rc.context"])?;
+    let active = match active.trim() {
+        "" => "none".to_string(),
+        active => active.to_string(),
+    };
+    let mut rows = vec![Row::new(tr!("none\tno context"), "none".to_string())];
+    rows.extend(
+        contexts
+            .into_iter()
+            .map(|(name, filter)| Row::new(format!("{}\t{}", name, filter), name.to_string())),
+    );
+    let name: String = rich_rofi(&tr!("Context ({})", active), rows)?;
+    task_command(vec!["context", &name]).context(tr!("switching context"))?;
+    Ok(())
+}
+
+/// The active context, if it has write defaults (`context.<name>.write`).
+pub fn write_context() -> Result<Option<WriteContext>> {
+    let (name, _) = task_command(vec!["_get", "rc.context"])?;
+    let name = name.trim();
+    if name.is_empty() || name == "none" {
+        return Ok(None);
+    }
+
+    Ok(get_config_var(&format!("context.{}.write", name))
+        .ok()
+        .filter(|defaults| !defaults.trim().is_empty())
+        .map(|defaults| WriteContext {
+            name: name.to_string(),
+            defaults,
+        }))
+}
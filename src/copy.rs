@@ -0,0 +1,47 @@
+//! Copying task details to the clipboard, via `wl-copy` on Wayland, `xclip` on X11, `pbcopy` on
+//! macOS and `clip` on Windows.
+
+use crate::{
+    platform, privacy,
+    widgets::{rich_rofi, Row},
+    TaskExt,
+};
+use anyhow::{bail, Context, Result};
+use std::{io::Write, process::Stdio};
+use task_hookrs::task::Task;
+
+pub fn copy_task(task: &Task) -> Result<()> {
+    let task = &privacy::masked(task);
+    let mut formats = vec![
+        Row::new(
+            tr!("Description: {}", task.description()),
+            task.description().to_string(),
+        ),
+        Row::new(format!("UUID: {}", task.uuid()), task.uuid().to_string()),
+    ];
+    if let Some(link) = task.link_annotations().first() {
+        let markdown = format!("[{}]({})", task.description(), link.description());
+        formats.push(Row::new(tr!("Markdown link: {}", markdown), markdown));
+    }
+    formats.push(Row::new(tr!("JSON"), serde_json::to_string_pretty(task)?));
+
+    let text: String = rich_rofi(&tr!("Copy"), formats)?;
+    to_clipboard(&text)
+}
+
+pub fn to_clipboard(text: &str) -> Result<()> {
+    let mut child = platform::clipboard()
+        .stdin(Stdio::piped())
+        .spawn()
+        .context(tr!("running clipboard command"))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(text.as_bytes())?;
+
+    if !child.wait()?.success() {
+        bail!(tr!("clipboard command failed"));
+    }
+    Ok(())
+}
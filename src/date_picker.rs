@@ -0,0 +1,103 @@
+//! A month calendar drawn as rofi rows, one week per row. Picking a week then asks for the day.
+//! Weeks start on the day from `weeks`.
+
+use crate::{
+    dates,
+    style::{Glyph, Style},
+    weeks,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::Result;
+use chrono::{offset::Local as LocalTime, Datelike, Duration, NaiveDate};
+
+enum Entry {
+    Previous,
+    Next,
+    Header,
+    Week(NaiveDate),
+}
+
+pub fn pick(style: &Style) -> Result<NaiveDate> {
+    let today = LocalTime::now().naive_local().date();
+    let mut month = first_of_month(today);
+
+    loop {
+        let next_month = dates::shift_months(month, 1);
+        let mut rows = vec![
+            Row::new(
+                format!(
+                    "{} {}",
+                    style.glyph(Glyph::ArrowLeft),
+                    tr!("Previous month")
+                ),
+                Entry::Previous,
+            ),
+            Row::new(
+                format!("{} {}", tr!("Next month"), style.glyph(Glyph::ArrowRight)),
+                Entry::Next,
+            ),
+            Row::header(header(), Entry::Header),
+        ];
+        let mut start = weeks::start_of_week(month, weeks::first_day());
+        while start < next_month {
+            rows.push(Row::new(
+                week_label(start, month, today),
+                Entry::Week(start),
+            ));
+            start += Duration::days(7);
+        }
+
+        match rich_rofi(&month.format("%B %Y").to_string(), rows)? {
+            Entry::Previous => month = dates::shift_months(month, -1),
+            Entry::Next => month = next_month,
+            Entry::Header => {}
+            Entry::Week(start) => {
+                if let Some(date) = pick_day(start, month)? {
+                    return Ok(date);
+                }
+            }
+        }
+    }
+}
+
+/// `None` goes back to the calendar.
+fn pick_day(start: NaiveDate, month: NaiveDate) -> Result<Option<NaiveDate>> {
+    let mut days: Vec<_> = (0..7)
+        .map(|offset| start + Duration::days(offset))
+        .filter(|day| day.month() == month.month())
+        .map(|day| Row::new(day.format("%a %e %b").to_string(), Some(day)))
+        .collect();
+    days.push(Row::new(tr!("Back"), None));
+    Ok(rich_rofi(&tr!("Which day?"), days)?)
+}
+
+/// The day names over the columns, like "Mo  Tu  We".
+fn header() -> String {
+    let names: Vec<_> = weeks::days(weeks::first_day())
+        .map(|day| format!("{:>3}", &day.to_string()[..2]))
+        .collect();
+    format!("    {}", names.join(" "))
+}
+
+/// Days outside the month are blank and today is starred.
+fn week_label(start: NaiveDate, month: NaiveDate, today: NaiveDate) -> String {
+    let cells: Vec<_> = (0..7)
+        .map(|offset| {
+            let day = start + Duration::days(offset);
+            if day.month() != month.month() {
+                "   ".to_string()
+            } else if day == today {
+                format!("{:>3}", format!("*{}", day.day()))
+            } else {
+                format!("{:>3}", day.day())
+            }
+        })
+        .collect();
+    // The ISO week most of the row's days are in
+    let midweek = start + Duration::days(3);
+    format!("W{:02} {}", midweek.iso_week().week(), cells.join(" "))
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month has a first day")
+}
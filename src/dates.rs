@@ -0,0 +1,411 @@
+//! Date presets for Wait, Due and Schedule, resolving the ones taskwarrior doesn't understand
+//! (like "next business day") to concrete dates locally.
+//!
+//! `w23` is the Monday of ISO week 23, this year or next if it has passed. `sprint+N` is the first
+//! day of the sprint N sprints from now and `eosprint+N` its last day, with the offset optional.
+//! Sprints are configured with `[sprint] start = "2026-01-05"` and `length = 14` (days).
+//!
+//! Phrases with spaces, which `wait:`/`due:` reject, are translated: `in 3 days`, `2 weeks from
+//! now`, `day after tomorrow`, `next friday`, `end of month` and the like.
+//!
+//! Holidays come from `holidays = ["2026-12-25", ...]` in the config plus any `holiday.*.date`
+//! entries in taskrc, such as the holiday files shipped with taskwarrior.
+
+use crate::{
+    config::{Config, SprintConfig},
+    date_picker, duration, task_command, weeks, widgets,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{
+    offset::Local as LocalTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday,
+};
+
+const NEXT_BUSINESS_DAY: &str = "next business day";
+const CALENDAR: &str = "calendar...";
+const ALL_DAY: &str = "all day";
+const TIME_PRESETS: [&str; 3] = ["09:00", "12:00", "17:00"];
+
+pub const WAIT_PRESETS: [&str; 6] = ["tomorrow", NEXT_BUSINESS_DAY, "1h", "2h", "4h", "monday"];
+pub const DUE_PRESETS: [&str; 6] = [
+    "today",
+    "tomorrow",
+    NEXT_BUSINESS_DAY,
+    "friday",
+    "eow",
+    "eosprint",
+];
+/// Multi-word phrases and the taskwarrior synonym they mean.
+const PHRASES: [(&str, &str); 6] = [
+    ("end of day", "eod"),
+    ("end of week", "eow"),
+    ("end of the week", "eow"),
+    ("end of month", "eom"),
+    ("end of the month", "eom"),
+    ("end of year", "eoy"),
+];
+
+pub const SCHEDULE_PRESETS: [&str; 5] =
+    ["today", "tomorrow", NEXT_BUSINESS_DAY, "monday", "sprint+1"];
+
+/// Asks for a date from the presets (or free text) and returns a value for `wait:`/`due:`.
+/// Durations like `1h30` are passed on as ISO 8601. Anything else that isn't one, like `2nd` or
+/// `20261020`, is left for taskwarrior to read as a date.
+pub fn pick_date(prompt: &str, presets: &[&str], config: &Config) -> Result<String> {
+    let mut presets = offered(presets, &config.sprint);
+    presets.push(CALENDAR.to_string());
+    let today = LocalTime::now().naive_local().date();
+
+    widgets::validated(prompt, &presets, |input| {
+        if input == CALENDAR {
+            let date = date_picker::pick(&config.style)?;
+            return Ok(date.format("%Y-%m-%d").to_string());
+        }
+        if input.trim().eq_ignore_ascii_case(NEXT_BUSINESS_DAY) {
+            let date = next_business_day(today, &holidays(config)?);
+            return Ok(date.format("%Y-%m-%d").to_string());
+        }
+        if let Some(date) = resolve_relative(input, today, &config.sprint)? {
+            return Ok(date.format("%Y-%m-%d").to_string());
+        }
+        if let Some(date) = resolve_phrase(input, today) {
+            return Ok(date);
+        }
+        if let Ok(duration) = duration::parse(input) {
+            return Ok(duration::to_iso(duration));
+        }
+        if input.trim().contains(char::is_whitespace) {
+            bail!(tr!(
+                "`{}` isn't a date taskwarrior understands",
+                input.trim()
+            ));
+        }
+        Ok(input.trim().to_string())
+    })
+}
+
+/// The presets worth showing: sprint dates only resolve once sprints are configured.
+fn offered(presets: &[&str], sprint: &SprintConfig) -> Vec<String> {
+    presets
+        .iter()
+        .filter(|preset| sprint.start.is_some() || !preset.contains("sprint"))
+        .map(|preset| preset.to_string())
+        .collect()
+}
+
+/// Translates multi-word phrases to a taskwarrior synonym or a `YYYY-MM-DD` date, or `None` for
+/// anything else.
+fn resolve_phrase(input: &str, today: NaiveDate) -> Option<String> {
+    let words: Vec<_> = input.split_whitespace().collect();
+    let input = words.join(" ").to_lowercase();
+    if let Some((_, synonym)) = PHRASES.iter().find(|(phrase, _)| *phrase == input) {
+        return Some(synonym.to_string());
+    }
+    // Taskwarrior's `sow`/`som` changed meaning between versions, so these are resolved here
+    let date = match input.as_str() {
+        "day after tomorrow" => Some(today + Duration::days(2)),
+        "next week" | "start of next week" => {
+            Some(weeks::start_of_week(today, weeks::first_day()) + Duration::days(7))
+        }
+        "next month" | "start of next month" => Some(shift_months(today, 1).with_day(1)?),
+        _ => None,
+    };
+    if let Some(date) = date {
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+
+    let words: Vec<_> = input.split(' ').collect();
+    match words.as_slice() {
+        // "next friday" and "this friday" are both the coming one, which is what `friday` means
+        ["next" | "this", day] if day.parse::<Weekday>().is_ok() => Some(day.to_string()),
+        ["in", count, unit] | [count, unit, "from", "now"] => {
+            let count: i64 = count.parse().ok()?;
+            let date = match unit.trim_end_matches('s') {
+                "day" => today + Duration::days(count),
+                "week" => today + Duration::weeks(count),
+                "month" => shift_months(today, count as i32),
+                "year" => shift_months(today, count as i32 * 12),
+                _ => return None,
+            };
+            Some(date.format("%Y-%m-%d").to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Resolves week numbers and sprint offsets, or `None` for anything else.
+fn resolve_relative(
+    input: &str,
+    today: NaiveDate,
+    sprint: &SprintConfig,
+) -> Result<Option<NaiveDate>> {
+    let input = input.trim().to_lowercase();
+
+    if let Some(week) = input.strip_prefix('w').and_then(|w| w.parse::<u32>().ok()) {
+        let year = today.iso_week().year();
+        let monday = |year| {
+            NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                .ok_or_else(|| anyhow!(tr!("There is no week {} in {}", week, year)))
+        };
+        let date = monday(year)?;
+        // A week that is already over means next year's
+        if date + Duration::days(6) < today {
+            return monday(year + 1).map(Some);
+        }
+        return Ok(Some(date));
+    }
+
+    let (end, offset) = if let Some(offset) = input.strip_prefix("eosprint") {
+        (true, offset)
+    } else if let Some(offset) = input.strip_prefix("sprint") {
+        (false, offset)
+    } else {
+        return Ok(None);
+    };
+    let offset: i64 = match offset.trim() {
+        "" => 0,
+        offset => offset
+            .parse()
+            .map_err(|_| anyhow!(tr!("`{}` is not a sprint date like sprint+1", input)))?,
+    };
+
+    let first = sprint
+        .start
+        .as_deref()
+        .ok_or_else(|| anyhow!(tr!("Set [sprint] start in the config to use sprint dates")))?;
+    let first = NaiveDate::parse_from_str(first, "%Y-%m-%d")
+        .map_err(|_| anyhow!(tr!("sprint start `{}` is not a YYYY-MM-DD date", first)))?;
+    if sprint.length < 1 {
+        bail!(tr!("Sprints must be at least one day long"));
+    }
+
+    let current = (today - first).num_days().div_euclid(sprint.length);
+    let start = first + Duration::days((current + offset) * sprint.length);
+    Ok(Some(if end {
+        start + Duration::days(sprint.length - 1)
+    } else {
+        start
+    }))
+}
+
+/// Optionally adds a time of day to a date from `pick_date`, giving a full datetime for `due:`.
+/// Named dates like `friday` are resolved with `task calc` first; durations are left alone.
+pub fn pick_time(date: &str) -> Result<String> {
+    if date.starts_with('P') {
+        return Ok(date.to_string());
+    }
+    let mut presets = vec![ALL_DAY.to_string()];
+    presets.extend(TIME_PRESETS.iter().map(|p| p.to_string()));
+    let time = widgets::validated(&tr!("Time on {}? (HH:MM)", date), &presets, |input| {
+        if input == ALL_DAY {
+            return Ok(None);
+        }
+        NaiveTime::parse_from_str(input.trim(), "%H:%M")
+            .map(Some)
+            .map_err(|_| anyhow!(tr!("`{}` is not a time like 17:30", input)))
+    })?;
+    let time = match time {
+        Some(time) => time,
+        None => return Ok(date.to_string()),
+    };
+
+    let day = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(day) => day,
+        Err(_) => {
+            let (resolved, _) = task_command(vec!["calc", date])?;
+            NaiveDate::parse_from_str(resolved.trim().get(..10).unwrap_or_default(), "%Y-%m-%d")
+                .map_err(|_| anyhow!(tr!("taskwarrior couldn't resolve `{}` to a date", date)))?
+        }
+    };
+    Ok(day.and_time(time).format("%Y-%m-%dT%H:%M").to_string())
+}
+
+/// A date value resolved with `task calc`, in local time, or `None` for durations.
+pub fn calc(value: &str) -> Result<Option<NaiveDateTime>> {
+    let (resolved, _) = task_command(vec!["calc", value])?;
+    Ok(NaiveDateTime::parse_from_str(resolved.trim(), "%Y-%m-%dT%H:%M:%S").ok())
+}
+
+/// The same day `months` later, or the month's last day when it is shorter.
+pub fn shift_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let index = date.year() * 12 + date.month0() as i32 + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .expect("every month has a first day")
+}
+
+pub fn next_business_day(from: NaiveDate, holidays: &[NaiveDate]) -> NaiveDate {
+    let mut date = from + Duration::days(1);
+    while is_weekend(date) || holidays.contains(&date) {
+        date += Duration::days(1);
+    }
+    date
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+pub fn holidays(config: &Config) -> Result<Vec<NaiveDate>> {
+    let mut holidays = config
+        .holidays
+        .iter()
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| anyhow!(tr!("holiday `{}` is not a YYYY-MM-DD date", date)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (settings, _) = task_command(vec!["_show"])?;
+    holidays.extend(settings.lines().filter_map(|line| {
+        let (key, value) = split_setting(line)?;
+        if key.starts_with("holiday.") && key.ends_with(".date") {
+            NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+        } else {
+            None
+        }
+    }));
+
+    Ok(holidays)
+}
+
+pub fn split_setting(line: &str) -> Option<(&str, &str)> {
+    let idx = line.find('=')?;
+    Some((&line[..idx], &line[idx + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap()
+    }
+
+    fn sprints() -> SprintConfig {
+        SprintConfig {
+            start: Some("2026-01-05".to_string()),
+            length: 14,
+        }
+    }
+
+    fn relative(input: &str) -> Option<NaiveDate> {
+        resolve_relative(input, date("2026-10-14"), &sprints()).unwrap()
+    }
+
+    #[test]
+    fn week_numbers() {
+        assert_eq!(relative("w43"), Some(date("2026-10-19")));
+        assert_eq!(relative("W42"), Some(date("2026-10-12")));
+        // Week 41 is over, so it's next year's
+        assert_eq!(relative("w41"), Some(date("2027-10-11")));
+        assert!(resolve_relative("w54", date("2026-10-14"), &sprints()).is_err());
+    }
+
+    #[test]
+    fn sprint_offsets() {
+        assert_eq!(relative("sprint"), Some(date("2026-10-12")));
+        assert_eq!(relative("eosprint"), Some(date("2026-10-25")));
+        assert_eq!(relative("sprint+1"), Some(date("2026-10-26")));
+        assert_eq!(relative("eosprint+1"), Some(date("2026-11-08")));
+        assert_eq!(relative("sprint-1"), Some(date("2026-09-28")));
+    }
+
+    #[test]
+    fn sprints_need_a_start() {
+        let today = date("2026-10-14");
+        assert!(resolve_relative("sprint+1", today, &SprintConfig::default()).is_err());
+        assert!(resolve_relative("sprint+x", today, &sprints()).is_err());
+    }
+
+    #[test]
+    fn other_input_is_not_relative() {
+        assert_eq!(relative("tomorrow"), None);
+        assert_eq!(relative("wednesday"), None);
+    }
+
+    #[test]
+    fn sprint_presets_need_sprints() {
+        assert!(!offered(&DUE_PRESETS, &SprintConfig::default()).contains(&"eosprint".to_string()));
+        assert!(offered(&DUE_PRESETS, &sprints()).contains(&"eosprint".to_string()));
+        assert_eq!(
+            offered(&SCHEDULE_PRESETS, &SprintConfig::default()),
+            ["today", "tomorrow", NEXT_BUSINESS_DAY, "monday"]
+        );
+    }
+
+    #[test]
+    fn business_days_skip_weekends() {
+        assert_eq!(
+            next_business_day(date("2026-10-14"), &[]),
+            date("2026-10-15")
+        );
+        assert_eq!(
+            next_business_day(date("2026-10-16"), &[]),
+            date("2026-10-19")
+        );
+        assert_eq!(
+            next_business_day(date("2026-10-17"), &[]),
+            date("2026-10-19")
+        );
+    }
+
+    #[test]
+    fn business_days_skip_holidays() {
+        let holidays = [date("2026-12-24"), date("2026-12-25"), date("2026-12-28")];
+        assert_eq!(
+            next_business_day(date("2026-12-23"), &holidays),
+            date("2026-12-29")
+        );
+    }
+
+    fn phrase(input: &str) -> Option<String> {
+        resolve_phrase(input, date("2026-10-14"))
+    }
+
+    #[test]
+    fn phrases_with_synonyms() {
+        for (input, synonym) in PHRASES {
+            assert_eq!(phrase(input).as_deref(), Some(synonym), "{}", input);
+        }
+        assert_eq!(phrase("End  of the Month").as_deref(), Some("eom"));
+    }
+
+    #[test]
+    fn phrases_resolved_here() {
+        assert_eq!(phrase("day after tomorrow").as_deref(), Some("2026-10-16"));
+        assert_eq!(phrase("next week").as_deref(), Some("2026-10-19"));
+        assert_eq!(phrase("start of next week").as_deref(), Some("2026-10-19"));
+        assert_eq!(phrase("next month").as_deref(), Some("2026-11-01"));
+        assert_eq!(phrase("start of next month").as_deref(), Some("2026-11-01"));
+        assert_eq!(phrase("next friday").as_deref(), Some("friday"));
+        assert_eq!(phrase("this friday").as_deref(), Some("friday"));
+    }
+
+    #[test]
+    fn phrases_with_counts() {
+        assert_eq!(phrase("in 3 days").as_deref(), Some("2026-10-17"));
+        assert_eq!(phrase("in 1 day").as_deref(), Some("2026-10-15"));
+        assert_eq!(phrase("2 weeks from now").as_deref(), Some("2026-10-28"));
+        assert_eq!(phrase("in 1 month").as_deref(), Some("2026-11-14"));
+        assert_eq!(phrase("in 2 years").as_deref(), Some("2028-10-14"));
+    }
+
+    #[test]
+    fn other_phrases_are_left_alone() {
+        assert_eq!(phrase("tomorrow"), None);
+        assert_eq!(phrase("in 3 fortnights"), None);
+        assert_eq!(phrase("in a week"), None);
+        assert_eq!(phrase("next blursday"), None);
+    }
+
+    #[test]
+    fn months_clamp_to_the_last_day() {
+        assert_eq!(shift_months(date("2026-01-31"), 1), date("2026-02-28"));
+        assert_eq!(shift_months(date("2028-01-31"), 1), date("2028-02-29"));
+        assert_eq!(shift_months(date("2026-03-31"), -1), date("2026-02-28"));
+        assert_eq!(shift_months(date("2026-11-30"), 3), date("2027-02-28"));
+        assert_eq!(shift_months(date("2026-10-14"), -12), date("2025-10-14"));
+    }
+}
@@ -0,0 +1,118 @@
+//! The `org.taskwarrior_rofi` D-Bus service, run on the session bus by `--watch`, so desktop
+//! environments and other programs can drive the menu without starting a process:
+//!
+//! - `ShowMenu()` reopens the menu
+//! - `QuickAdd(text)` adds a task, with `--` separating annotations as in Add, and returns its UUID
+//! - `Done(uuid)` completes a task
+//! - `ActiveTask`, a property, is the UUID of the first started task, or empty
+//! - `Mutated(event, payload)`, a signal, comes with each change, carrying the same JSON payload
+//!   as the webhook (see `webhook`)
+//!
+//! It lives at `/org/taskwarrior_rofi`; try it with
+//! `busctl --user call org.taskwarrior_rofi /org/taskwarrior_rofi org.taskwarrior_rofi ShowMenu`.
+//! Changes made by other runs of this tool send `Mutated` too, from a connection of their own.
+
+use crate::{add_task_quietly, is_uuid, query_sorted, split_add_input, task_command, watch::Wake};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::sync::{mpsc::Sender, OnceLock};
+use zbus::{blocking::Connection, fdo, interface, object_server::SignalEmitter};
+
+const NAME: &str = "org.taskwarrior_rofi";
+const PATH: &str = "/org/taskwarrior_rofi";
+
+/// The watcher's connection, once it has one.
+static SERVICE: OnceLock<Connection> = OnceLock::new();
+
+struct Service {
+    wakes: Sender<Wake>,
+}
+
+#[interface(name = "org.taskwarrior_rofi")]
+impl Service {
+    fn show_menu(&self) {
+        let _ = self.wakes.send(Wake::Menu);
+    }
+
+    fn quick_add(&self, text: &str) -> fdo::Result<String> {
+        refuse_if_read_only()?;
+        let (task_text, annotations) = split_add_input(text).map_err(failed)?;
+        add_task_quietly(&task_text, &annotations).map_err(failed)
+    }
+
+    fn done(&self, uuid: &str) -> fdo::Result<()> {
+        refuse_if_read_only()?;
+        // Anything else there would be a filter, which could match every task
+        if !is_uuid(uuid) {
+            return Err(fdo::Error::InvalidArgs(tr!("`{}` is not a UUID", uuid)));
+        }
+        task_command(vec![uuid, "done"])
+            .context(tr!("completing task"))
+            .map_err(failed)?;
+        Ok(())
+    }
+
+    // Nothing would notice a task started from the command line, so there's no change signal
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn active_task(&self) -> fdo::Result<String> {
+        let active = query_sorted("+ACTIVE").map_err(failed)?;
+        Ok(active
+            .first()
+            .map(|task| task.uuid().to_string())
+            .unwrap_or_default())
+    }
+
+    #[zbus(signal)]
+    async fn mutated(emitter: &SignalEmitter<'_>, event: &str, payload: &str) -> zbus::Result<()>;
+}
+
+/// Claims the name on the session bus and serves the service, plus the search provider with that
+/// feature. Calls run on zbus's own thread; anything that opens rofi goes through `wakes`.
+pub fn start(wakes: Sender<Wake>) -> Result<()> {
+    let builder = zbus::blocking::connection::Builder::session()?.name(NAME)?;
+    #[cfg(feature = "search-provider")]
+    let builder = builder.serve_at(
+        crate::search::PATH,
+        crate::search::SearchProvider {
+            wakes: wakes.clone(),
+        },
+    )?;
+    let connection = builder
+        .serve_at(PATH, Service { wakes })?
+        .build()
+        .with_context(|| tr!("claiming {} on the session bus", NAME))?;
+    let _ = SERVICE.set(connection);
+    Ok(())
+}
+
+/// Sends `Mutated` for a webhook payload. There's nothing to report a failure to, so a missing
+/// session bus is ignored.
+pub fn mutated(payload: &Value) {
+    static OWN: OnceLock<Option<Connection>> = OnceLock::new();
+    let connection = match SERVICE.get() {
+        Some(connection) => connection,
+        None => match OWN.get_or_init(|| Connection::session().ok()) {
+            Some(connection) => connection,
+            None => return,
+        },
+    };
+    let event = payload["event"].as_str().unwrap_or_default();
+    let _ = connection.emit_signal(
+        None::<&str>,
+        PATH,
+        NAME,
+        "Mutated",
+        &(event, payload.to_string()),
+    );
+}
+
+fn refuse_if_read_only() -> fdo::Result<()> {
+    if crate::watch::read_only().map_err(failed)? {
+        return Err(fdo::Error::AccessDenied(tr!("This menu is read-only")));
+    }
+    Ok(())
+}
+
+pub fn failed(err: anyhow::Error) -> fdo::Error {
+    fdo::Error::Failed(format!("{:#}", err))
+}
@@ -0,0 +1,148 @@
+//! Shorthand durations like `90m`, `1h30` or `2d`, validated locally so a typo asks again
+//! instead of ending up in a task description.
+//!
+//! Units are `w`, `d`, `h`, `m` (or `min`) and `s`. A trailing number without a unit takes the
+//! next smaller unit, so `1h30` is an hour and a half.
+
+use crate::widgets;
+use anyhow::{anyhow, bail, Result};
+use chrono::Duration;
+
+pub const ESTIMATE_PRESETS: [&str; 6] = ["15m", "30m", "1h", "2h", "4h", "1d"];
+
+const UNITS: [(&str, i64); 6] = [
+    ("w", 7 * 24 * 3600),
+    ("d", 24 * 3600),
+    ("h", 3600),
+    ("min", 60),
+    ("m", 60),
+    ("s", 1),
+];
+
+pub fn parse(input: &str) -> Result<Duration> {
+    let input = input.trim().to_lowercase();
+    let mut rest = input.as_str();
+    let mut seconds = 0.0;
+    let mut last_unit: Option<i64> = None;
+
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            bail!(tr!("`{}` is not a duration like 90m, 1h30 or 2d", input));
+        }
+        let number: f64 = rest[..number_len]
+            .parse()
+            .map_err(|_| anyhow!(tr!("`{}` is not a duration like 90m, 1h30 or 2d", input)))?;
+        rest = rest[number_len..].trim_start();
+
+        let unit = match UNITS.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, unit)) => {
+                rest = rest[name.len()..].trim_start();
+                *unit
+            }
+            None if rest.is_empty() => match last_unit {
+                Some(last) => UNITS
+                    .iter()
+                    .map(|(_, unit)| *unit)
+                    .find(|unit| *unit < last)
+                    .ok_or_else(|| anyhow!(tr!("`{}` has a number without a unit", input)))?,
+                None => bail!(tr!("`{}` needs a unit, like 30m or 2h", input)),
+            },
+            None => bail!(tr!("`{}` is not a duration like 90m, 1h30 or 2d", input)),
+        };
+        seconds += number * unit as f64;
+        last_unit = Some(unit);
+    }
+
+    if last_unit.is_none() {
+        bail!(tr!("No duration given"));
+    }
+    Ok(Duration::seconds(seconds.round() as i64))
+}
+
+/// The compact form, like `1h30m`, used for display and for string UDAs such as `estimate`.
+pub fn format(duration: Duration) -> String {
+    let mut seconds = duration.num_seconds();
+    let mut out = String::new();
+    for (name, unit) in UNITS
+        .iter()
+        .filter(|(name, _)| *name != "min" && *name != "w")
+    {
+        if seconds >= *unit {
+            out.push_str(&format!("{}{}", seconds / unit, name));
+            seconds %= unit;
+        }
+    }
+    if out.is_empty() {
+        out.push_str("0m");
+    }
+    out
+}
+
+/// The ISO 8601 form, like `PT1H30M`, which taskwarrior accepts anywhere it takes a duration.
+pub fn to_iso(duration: Duration) -> String {
+    let mut seconds = duration.num_seconds();
+    let days = seconds / (24 * 3600);
+    seconds %= 24 * 3600;
+    let mut out = "P".to_string();
+    if days > 0 {
+        out.push_str(&format!("{}D", days));
+    }
+    if seconds > 0 || days == 0 {
+        out.push('T');
+        for (suffix, unit) in [("H", 3600), ("M", 60), ("S", 1)] {
+            if seconds >= unit || (unit == 1 && out.ends_with('T')) {
+                out.push_str(&format!("{}{}", seconds / unit, suffix));
+                seconds %= unit;
+            }
+        }
+    }
+    out
+}
+
+/// Asks for a duration, asking again with the problem in the prompt until it parses.
+pub fn pick_duration(prompt: &str, presets: &[&str]) -> Result<Duration> {
+    let presets: Vec<_> = presets.iter().map(|p| p.to_string()).collect();
+    widgets::validated(prompt, &presets, parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_units() {
+        assert_eq!(parse("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse("45min").unwrap(), Duration::minutes(45));
+    }
+
+    #[test]
+    fn trailing_number_takes_the_next_unit() {
+        assert_eq!(parse("1h30").unwrap(), Duration::minutes(90));
+        assert_eq!(parse("1H 30M").unwrap(), Duration::minutes(90));
+    }
+
+    // pick_date hands anything that doesn't parse on to taskwarrior as a date
+    #[test]
+    fn dates_are_not_durations() {
+        assert!(parse("2nd").is_err());
+        assert!(parse("22nd").is_err());
+        assert!(parse("20261020").is_err());
+    }
+
+    #[test]
+    fn no_unit() {
+        let err = parse("30").unwrap_err().to_string();
+        assert!(err.contains("needs a unit"), "{}", err);
+    }
+
+    #[test]
+    fn garbage() {
+        assert!(parse("soon").is_err());
+        assert!(parse("1x").is_err());
+        assert!(parse("").is_err());
+    }
+}
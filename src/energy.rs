@@ -0,0 +1,97 @@
+//! GTD-style "what can I do now?": tasks carry an `energy` UDA (`high`, `medium` or `low`) and the
+//! view offers only the ones that fit your current energy and the time you have, judged by the
+//! `estimate` UDA.
+
+use crate::{
+    duration, get_config_var, planner, query_sorted, quick, task_command,
+    widgets::{rich_rofi, Row},
+    App, TaskExt,
+};
+use anyhow::{bail, Context, Result};
+use task_hookrs::task::Task;
+
+const UDA: &str = "energy";
+const AVAILABLE_PRESETS: [&str; 5] = ["15m", "30m", "1h", "2h", "4h"];
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Energy {
+    Low,
+    Medium,
+    High,
+}
+
+impl Energy {
+    const ALL: [Energy; 3] = [Energy::High, Energy::Medium, Energy::Low];
+
+    fn value(self) -> &'static str {
+        match self {
+            Energy::Low => "low",
+            Energy::Medium => "medium",
+            Energy::High => "high",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|e| e.value() == value)
+    }
+}
+
+/// Sets or clears a task's energy level.
+pub fn set_energy(task: &Task) -> Result<()> {
+    require_uda()?;
+    let mut levels: Vec<_> = Energy::ALL
+        .iter()
+        .map(|e| Row::new(e.value().to_string(), e.value()))
+        .collect();
+    levels.push(Row::new(tr!("Clear"), ""));
+    let level: &str = rich_rofi(&tr!("Energy needed"), levels)?;
+    task_command(vec![
+        &task.uuid().to_string(),
+        "mod",
+        &format!("{}:{}", UDA, level),
+    ])
+    .context(tr!("modifying energy"))?;
+    Ok(())
+}
+
+pub fn what_now(app: &App) -> Result<()> {
+    require_uda()?;
+    let levels = Energy::ALL
+        .iter()
+        .map(|e| Row::new(e.value().to_string(), *e))
+        .collect();
+    let energy: Energy = rich_rofi(&tr!("How much energy do you have?"), levels)?;
+    let available =
+        duration::pick_duration(&tr!("How much time do you have?"), &AVAILABLE_PRESETS)?;
+    let hours = available.num_seconds() as f64 / 3600.0;
+
+    let default_estimate = app.config.scheduler.default_estimate;
+    let tasks: Vec<_> = query_sorted("status:pending -BLOCKED")?
+        .into_iter()
+        // Tasks without a level fit any energy
+        .filter(|task| {
+            task.uda_string(UDA)
+                .and_then(|value| Energy::parse(&value))
+                .is_none_or(|needed| needed <= energy)
+        })
+        .filter(|task| planner::estimate_hours(task).unwrap_or(default_estimate) <= hours)
+        .collect();
+    if tasks.is_empty() {
+        bail!(tr!(
+            "Nothing fits {} energy and {}",
+            energy.value(),
+            duration::format(available)
+        ));
+    }
+
+    quick::pick_and_act(&tr!("What can I do now?"), tasks, &app.config.style)
+}
+
+fn require_uda() -> Result<()> {
+    if get_config_var(&format!("uda.{}.type", UDA)).is_err() {
+        bail!(tr!(
+            "Define the energy UDA in taskrc first: uda.energy.type=string, uda.energy.values=high,medium,low"
+        ));
+    }
+    Ok(())
+}
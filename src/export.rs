@@ -0,0 +1,79 @@
+//! `task export` read as a stream, so tasks can be worked on while taskwarrior is still writing
+//! the rest. With `json.array` off every task is its own JSON value, which serde_json parses one
+//! at a time straight from the pipe.
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{de::IoRead, Deserializer, StreamDeserializer};
+use std::{
+    io::{BufReader, Read},
+    process::{Child, ChildStdout, Command, Stdio},
+};
+use task_hookrs::task::Task;
+
+/// The tasks matching a filter, in export order. An error from taskwarrior comes last, once the
+/// output has been read.
+pub struct Tasks {
+    child: Child,
+    stream: StreamDeserializer<'static, IoRead<BufReader<ChildStdout>>, Task>,
+    done: bool,
+}
+
+pub fn stream(filter: &str) -> Result<Tasks> {
+    let mut command = Command::new("task");
+    command.arg("rc.json.array=off");
+    if !filter.trim().is_empty() {
+        command.arg(filter);
+    }
+    let mut child = command
+        .arg("export")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(tr!("running task export"))?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    Ok(Tasks {
+        child,
+        stream: Deserializer::from_reader(BufReader::new(stdout)).into_iter(),
+        done: false,
+    })
+}
+
+/// Every task matching a filter, in export order. Unlike `tw::query` this doesn't rely on
+/// `export` printing an array, which taskwarrior only does by default from 2.6 on.
+pub fn query(filter: &str) -> Result<Vec<Task>> {
+    stream(filter)?.collect()
+}
+
+impl Iterator for Tasks {
+    type Item = Result<Task>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.stream.next() {
+            Some(Ok(task)) => Some(Ok(task)),
+            Some(Err(err)) => {
+                self.done = true;
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                Some(Err(anyhow!(tr!("parsing task export: {}", err))))
+            }
+            None => {
+                self.done = true;
+                let status = match self.child.wait() {
+                    Ok(status) => status,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                if status.success() {
+                    return None;
+                }
+                let mut stderr = String::new();
+                if let Some(mut pipe) = self.child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr);
+                }
+                Some(Err(anyhow!(tr!("task export failed: {}", stderr.trim()))))
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Multi-step flows that survive a failure halfway through.
+//!
+//! Once a step has changed something, a later failure doesn't just end with an error: the user
+//! sees which steps went through and can retry or skip the failed one, or use one of the flow's
+//! recovery actions (like looking at the task that was already created).
+
+use crate::{
+    back_on_escape,
+    widgets::{self, rich_rofi, Row},
+};
+use anyhow::Result;
+
+type Action<'a> = Box<dyn FnMut() -> Result<()> + 'a>;
+
+#[derive(Default)]
+pub struct Flow<'a> {
+    steps: Vec<(String, Action<'a>)>,
+    recoveries: Vec<(String, Action<'a>)>,
+}
+
+enum Choice {
+    Retry,
+    Skip,
+    Recovery(usize),
+    Stop,
+}
+
+impl<'a> Flow<'a> {
+    pub fn step(mut self, name: String, step: impl FnMut() -> Result<()> + 'a) -> Self {
+        self.steps.push((name, Box::new(step)));
+        self
+    }
+
+    /// Offered after a failure, once at least one step has succeeded.
+    pub fn recovery(mut self, name: String, action: impl FnMut() -> Result<()> + 'a) -> Self {
+        self.recoveries.push((name, Box::new(action)));
+        self
+    }
+
+    pub fn run(mut self) -> Result<()> {
+        let mut completed: Vec<String> = vec![];
+        let mut idx = 0;
+        while idx < self.steps.len() {
+            let err = match (self.steps[idx].1)() {
+                Ok(()) => {
+                    completed.push(self.steps[idx].0.clone());
+                    idx += 1;
+                    continue;
+                }
+                // Nothing has changed yet, so a plain error says it all
+                Err(err) if completed.is_empty() => return Err(err),
+                Err(err) => err,
+            };
+
+            loop {
+                match self.recover(&self.steps[idx].0, &err, &completed)? {
+                    Choice::Retry => break,
+                    Choice::Skip => {
+                        idx += 1;
+                        break;
+                    }
+                    Choice::Recovery(recovery) => {
+                        // Escaping out of a recovery action comes back to this menu
+                        if let Err(err) = back_on_escape((self.recoveries[recovery].1)()) {
+                            widgets::message(&tr!("Error: {}", err))?;
+                        }
+                    }
+                    Choice::Stop => {
+                        return Err(err.context(tr!("stopped after: {}", completed.join(", "))))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn recover(&self, failed: &str, err: &anyhow::Error, completed: &[String]) -> Result<Choice> {
+        let mut rows = vec![Row::header(
+            tr!("Done: {}", completed.join(", ")),
+            Choice::Stop,
+        )];
+        rows.push(Row::header(
+            tr!("Failed: {} ({})", failed, err),
+            Choice::Stop,
+        ));
+        rows.push(Row::new(tr!("Retry {}", failed), Choice::Retry));
+        rows.push(Row::new(tr!("Skip {}", failed), Choice::Skip));
+        rows.extend(
+            self.recoveries
+                .iter()
+                .enumerate()
+                .map(|(idx, (name, _))| Row::new(name.clone(), Choice::Recovery(idx))),
+        );
+        rows.push(Row::new(tr!("Stop here"), Choice::Stop));
+
+        match rich_rofi(&tr!("Something went wrong"), rows) {
+            Ok(choice) => Ok(choice),
+            Err(rofi::Error::Interrupted) => Ok(Choice::Stop),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
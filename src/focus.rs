@@ -0,0 +1,68 @@
+//! Focus mode: one chosen task shown in the menu header and `taskwarrior-rofi status`, with quick
+//! done and stop-focus actions at the top of the menu.
+
+use crate::{
+    export, session,
+    state::State,
+    webhook,
+    widgets::{rich_rofi, Row},
+    MapFailure,
+};
+use anyhow::Result;
+use chrono::{offset::Local as LocalTime, NaiveDateTime};
+use task_hookrs::{status::TaskStatus, task::Task, tw};
+
+/// The focused task, if there is one and it is still pending.
+pub fn focused_task(state: &State) -> Result<Option<Task>> {
+    let uuid = match &state.focus {
+        Some(uuid) => uuid,
+        None => return Ok(None),
+    };
+    Ok(export::query(uuid)?
+        .into_iter()
+        .find(|task| *task.status() == TaskStatus::Pending))
+}
+
+pub fn focus(mut task: Task, state: &mut State) -> Result<()> {
+    let choices = vec![
+        Row::new(tr!("Focus and start"), true),
+        Row::new(tr!("Focus"), false),
+    ];
+    if rich_rofi(task.description(), choices)? && task.start().is_none() {
+        task.set_start(Some(LocalTime::now().naive_local()));
+        tw::save(Some(&task)).map_failure()?;
+        webhook::saved(&[&task]);
+    }
+
+    state.focus = Some(task.uuid().to_string());
+    state.save()
+}
+
+pub fn done(task: Task, state: &mut State) -> Result<()> {
+    let mut done = task.clone();
+    *done.status_mut() = TaskStatus::Completed;
+    tw::save(Some(&done)).map_failure()?;
+    webhook::saved(&[&done]);
+    session::saved(&task, &done);
+    state.focus = None;
+    state.save()
+}
+
+pub fn unfocus(task: Task, state: &mut State) -> Result<()> {
+    if task.start().is_some() {
+        let mut stopped = task.clone();
+        stopped.set_start::<NaiveDateTime>(None);
+        tw::save(Some(&stopped)).map_failure()?;
+        webhook::saved(&[&stopped]);
+        session::saved(&task, &stopped);
+    }
+    state.focus = None;
+    state.save()
+}
+
+/// A single line for status bars like polybar or waybar; empty when nothing is focused.
+pub fn status_line() -> Result<String> {
+    Ok(focused_task(&State::load()?)?
+        .map(|task| task.description().to_string())
+        .unwrap_or_default())
+}
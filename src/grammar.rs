@@ -0,0 +1,259 @@
+//! Quick-add's capture syntax. Add's input is split into the task and its annotations on the
+//! annotation separator, and words of the task starting with a sigil become the matching
+//! taskwarrior attribute:
+//!
+//! ```toml
+//! [add.grammar]
+//! annotation = "--"   # call mom -- about sunday
+//! project = "@"       # @home      -> project:home
+//! tag = "#"           # #errand    -> +errand
+//! priority = "!"      # !p1        -> priority:H
+//! priorities = { p1 = "H", p2 = "M", p3 = "L" }
+//! ```
+//!
+//! By default annotations follow `--` and `+` tags as taskwarrior itself does. There are no project
+//! or priority sigils unless set, so words like `@alice` stay in the description. Without
+//! `priorities` the priority sigil takes `H`, `M` and `L` themselves. The grammar is checked when
+//! the menu starts, so a clash between sigils is reported up front rather than as a strange task.
+
+use crate::config::GrammarConfig;
+use anyhow::{anyhow, bail, Result};
+use std::{collections::BTreeMap, sync::OnceLock};
+
+static GRAMMAR: OnceLock<Grammar> = OnceLock::new();
+
+const PRIORITIES: [&str; 3] = ["H", "M", "L"];
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Project,
+    Tag,
+    Priority,
+}
+
+struct Grammar {
+    annotation: String,
+    /// Longest sigil first, so `++` wins over `+`.
+    rules: Vec<(String, Kind)>,
+    /// Lowercased words after the priority sigil.
+    priorities: BTreeMap<String, String>,
+}
+
+pub fn init(config: &GrammarConfig) -> Result<()> {
+    let _ = GRAMMAR.set(compile(config)?);
+    Ok(())
+}
+
+fn compile(config: &GrammarConfig) -> Result<Grammar> {
+    if config.annotation.trim().is_empty() {
+        bail!(tr!("add.grammar.annotation can't be empty"));
+    }
+    let mut rules = vec![];
+    for (name, sigil, kind) in [
+        ("project", &config.project, Kind::Project),
+        ("tag", &config.tag, Kind::Tag),
+        ("priority", &config.priority, Kind::Priority),
+    ] {
+        if sigil.is_empty() {
+            continue;
+        }
+        if sigil.contains(char::is_whitespace) {
+            bail!(tr!("add.grammar.{} `{}` contains a space", name, sigil));
+        }
+        if *sigil == config.annotation || rules.iter().any(|(other, _)| other == sigil) {
+            bail!(tr!("add.grammar.{} `{}` is already used", name, sigil));
+        }
+        rules.push((sigil.clone(), kind));
+    }
+    rules.sort_by_key(|(sigil, _)| std::cmp::Reverse(sigil.len()));
+
+    let mut priorities = BTreeMap::new();
+    for (word, priority) in &config.priorities {
+        let priority = priority.to_uppercase();
+        if !PRIORITIES.contains(&priority.as_str()) {
+            bail!(tr!(
+                "add.grammar.priorities maps `{}` to `{}`, which is not H, M or L",
+                word,
+                priority
+            ));
+        }
+        priorities.insert(word.to_lowercase(), priority);
+    }
+
+    Ok(Grammar {
+        annotation: config.annotation.clone(),
+        rules,
+        priorities,
+    })
+}
+
+/// The default grammar until `init` says otherwise.
+fn grammar() -> &'static Grammar {
+    GRAMMAR
+        .get_or_init(|| compile(&GrammarConfig::default()).expect("the default grammar is valid"))
+}
+
+/// Add's prompt, showing the annotation separator.
+pub fn hint() -> String {
+    format!("task {} annotation", grammar().annotation)
+}
+
+/// Add's input as the arguments for `task add` and the annotations.
+pub fn parse(input: &str) -> Result<(String, Vec<String>)> {
+    grammar().parse(input)
+}
+
+impl Grammar {
+    fn parse(&self, input: &str) -> Result<(String, Vec<String>)> {
+        let mut parts = input.split(self.annotation.as_str());
+        let task = parts
+            .next()
+            .ok_or_else(|| anyhow!(tr!("No input given to add")))?;
+        let words = task
+            .split_whitespace()
+            .map(|word| self.expand(word))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((
+            words.join(" "),
+            parts.map(|ann| ann.trim().to_string()).collect(),
+        ))
+    }
+
+    fn expand(&self, word: &str) -> Result<String> {
+        for (sigil, kind) in &self.rules {
+            let value = match word.strip_prefix(sigil.as_str()) {
+                Some(value) if !value.is_empty() => value,
+                _ => continue,
+            };
+            return Ok(match kind {
+                Kind::Project => format!("project:{}", value),
+                Kind::Tag => format!("+{}", value),
+                Kind::Priority => format!("priority:{}", self.priority(value)?),
+            });
+        }
+        Ok(word.to_string())
+    }
+
+    fn priority(&self, word: &str) -> Result<String> {
+        if let Some(priority) = self.priorities.get(&word.to_lowercase()) {
+            return Ok(priority.clone());
+        }
+        let upper = word.to_uppercase();
+        if self.priorities.is_empty() && PRIORITIES.contains(&upper.as_str()) {
+            return Ok(upper);
+        }
+        bail!(tr!("`{}` is not a priority", word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sigils() -> GrammarConfig {
+        GrammarConfig {
+            project: "@".to_string(),
+            priority: "!".to_string(),
+            ..GrammarConfig::default()
+        }
+    }
+
+    fn parse_with(config: GrammarConfig, input: &str) -> Result<(String, Vec<String>)> {
+        compile(&config)?.parse(input)
+    }
+
+    #[test]
+    fn default_grammar() {
+        let (task, annotations) = parse_with(
+            GrammarConfig::default(),
+            "call @alice +phone -- about sunday",
+        )
+        .unwrap();
+        assert_eq!(task, "call @alice +phone");
+        assert_eq!(annotations, vec!["about sunday"]);
+    }
+
+    #[test]
+    fn sigils_expand() {
+        let (task, annotations) = parse_with(sigils(), "fix fence @home +diy !h").unwrap();
+        assert_eq!(task, "fix fence project:home +diy priority:H");
+        assert!(annotations.is_empty());
+    }
+
+    #[test]
+    fn bare_sigils_stay() {
+        let (task, _) = parse_with(sigils(), "meet @ noon !").unwrap();
+        assert_eq!(task, "meet @ noon !");
+    }
+
+    #[test]
+    fn several_annotations() {
+        let (_, annotations) =
+            parse_with(sigils(), "call mom -- about sunday -- bring cake").unwrap();
+        assert_eq!(annotations, vec!["about sunday", "bring cake"]);
+    }
+
+    #[test]
+    fn longest_sigil_wins() {
+        let config = GrammarConfig {
+            project: "++".to_string(),
+            ..GrammarConfig::default()
+        };
+        let (task, _) = parse_with(config, "++home +errand").unwrap();
+        assert_eq!(task, "project:home +errand");
+    }
+
+    #[test]
+    fn clashing_sigils() {
+        let config = GrammarConfig {
+            project: "+".to_string(),
+            ..GrammarConfig::default()
+        };
+        assert!(compile(&config).is_err());
+        let config = GrammarConfig {
+            tag: "--".to_string(),
+            ..GrammarConfig::default()
+        };
+        assert!(compile(&config).is_err());
+    }
+
+    #[test]
+    fn bad_sigils() {
+        let config = GrammarConfig {
+            annotation: " ".to_string(),
+            ..GrammarConfig::default()
+        };
+        assert!(compile(&config).is_err());
+        let config = GrammarConfig {
+            project: "@ ".to_string(),
+            ..GrammarConfig::default()
+        };
+        assert!(compile(&config).is_err());
+    }
+
+    #[test]
+    fn priority_words() {
+        let mut config = sigils();
+        config.priorities = BTreeMap::from([
+            ("p1".to_string(), "h".to_string()),
+            ("p3".to_string(), "L".to_string()),
+        ]);
+        let (task, _) = parse_with(config, "file taxes !P1 and !p3").unwrap();
+        assert_eq!(task, "file taxes priority:H and priority:L");
+    }
+
+    #[test]
+    fn priority_words_replace_the_letters() {
+        let mut config = sigils();
+        config.priorities = BTreeMap::from([("p1".to_string(), "H".to_string())]);
+        assert!(parse_with(config, "file taxes !H").is_err());
+        assert!(parse_with(sigils(), "file taxes !urgent").is_err());
+    }
+
+    #[test]
+    fn priority_words_must_map_to_a_priority() {
+        let mut config = sigils();
+        config.priorities = BTreeMap::from([("p0".to_string(), "X".to_string())]);
+        assert!(compile(&config).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Dependencies between pending tasks, and exporting them as a Graphviz picture.
+
+use crate::{
+    index::TaskIndex,
+    paths,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    process::Command,
+};
+use task_hookrs::task::Task;
+
+/// Pending tasks indexed by uuid, with an edge from each task to every task it depends on.
+pub struct DependencyGraph {
+    tasks: TaskIndex,
+    edges: Vec<(String, String)>,
+    /// Tasks with an edge to another task in the graph.
+    blocked: HashSet<String>,
+}
+
+impl DependencyGraph {
+    pub fn from_tasks(tasks: Vec<Task>) -> Self {
+        let mut edges = vec![];
+        for task in &tasks {
+            for dependency in task.depends().into_iter().flatten() {
+                edges.push((task.uuid().to_string(), dependency.to_string()));
+            }
+        }
+        let tasks = TaskIndex::new(tasks);
+        let blocked = edges
+            .iter()
+            .filter(|(_, to)| tasks.contains(to))
+            .map(|(from, _)| from.clone())
+            .collect();
+        Self {
+            tasks,
+            edges,
+            blocked,
+        }
+    }
+
+    /// Whether the task depends on another task in the graph, one that is still pending.
+    pub fn is_blocked(&self, uuid: &str) -> bool {
+        self.blocked.contains(uuid)
+    }
+
+    /// Only edges between tasks in the graph are drawn, so finished dependencies drop out.
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            "digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n".to_string();
+        let mut tasks: Vec<_> = self.tasks.tasks().iter().collect();
+        tasks.sort_by_key(|task| task.uuid().to_string());
+        for task in tasks {
+            let uuid = task.uuid();
+            let label = match task.id() {
+                Some(id) => format!("{}: {}", id, task.description()),
+                None => task.description().to_string(),
+            };
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                uuid,
+                escape(&label)
+            ));
+        }
+        for (from, to) in &self.edges {
+            if self.tasks.contains(from) && self.tasks.contains(to) {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", from, to));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Asks for a project (or everything), renders its graph with `dot` and opens the image.
+pub fn export() -> Result<()> {
+    let pending = crate::export::query("status:pending")?;
+    let projects: BTreeSet<_> = pending
+        .iter()
+        .filter_map(|task| task.project().cloned())
+        .collect();
+
+    let mut scopes = vec![Row::new(tr!("All pending tasks"), None)];
+    scopes.extend(
+        projects
+            .into_iter()
+            .map(|project| Row::new(project.clone(), Some(project))),
+    );
+    let scope: Option<String> = rich_rofi(&tr!("Graph which tasks?"), scopes)?;
+
+    let tasks = match &scope {
+        Some(project) => crate::export::query(&format!("status:pending project:{}", project))?,
+        None => pending,
+    };
+    let graph = DependencyGraph::from_tasks(tasks);
+    if graph.edges.is_empty() {
+        bail!(tr!("These tasks have no dependencies"));
+    }
+
+    let dir = paths::cache_dir()?;
+    fs::create_dir_all(&dir)?;
+    let dot_path = dir.join("dependencies.dot");
+    let svg_path = dir.join("dependencies.svg");
+    fs::write(&dot_path, graph.to_dot())?;
+
+    let status = Command::new("dot")
+        .arg("-Tsvg")
+        .arg("-o")
+        .arg(&svg_path)
+        .arg(&dot_path)
+        .status()
+        .with_context(|| {
+            tr!(
+                "running dot, is graphviz installed? The graph is in {}",
+                dot_path.display()
+            )
+        })?;
+    if !status.success() {
+        bail!(tr!("dot failed on {}", dot_path.display()));
+    }
+
+    open::that(&svg_path).context(tr!("opening the rendered graph"))?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Translations of user-visible strings.
+//!
+//! Strings are looked up by their English text in `~/.config/taskwarrior-rofi/locales/<lang>.toml`,
+//! a flat table of English to translated text:
+//!
+//! ```toml
+//! "Choose an action" = "Aktion wählen"
+//! "Mods for task {}" = "Änderungen für Aufgabe {}"
+//! ```
+//!
+//! The language is `locale` from the config, otherwise `LC_ALL`, `LC_MESSAGES` or `LANG`. For a
+//! value like `de_DE.UTF-8`, `de_DE.toml` is tried before `de.toml`. Missing files and missing
+//! entries fall back to English.
+
+use crate::paths;
+use anyhow::{Context, Result};
+use std::{collections::HashMap, env, fmt::Display, fs, sync::OnceLock};
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Translates a string, substituting any arguments for its `{}` placeholders in order.
+macro_rules! tr {
+    ($text:expr) => {
+        $crate::i18n::translate($text)
+    };
+    ($text:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::translate_with($text, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+pub fn init(locale: Option<&str>) -> Result<()> {
+    let locale = match locale {
+        Some(locale) => locale.to_string(),
+        None => match ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+        {
+            Some(locale) => locale,
+            None => return Ok(()),
+        },
+    };
+
+    let full = locale.split('.').next().unwrap_or_default();
+    let language = full.split('_').next().unwrap_or_default();
+    let dir = paths::locales_dir()?;
+    for name in &[full, language] {
+        let path = dir.join(format!("{}.toml", name));
+        if path.exists() {
+            let contents = fs::read_to_string(&path)?;
+            let catalog =
+                toml::from_str(&contents).with_context(|| format!("parsing {}", path.display()))?;
+            let _ = CATALOG.set(catalog);
+            break;
+        }
+    }
+    Ok(())
+}
+
+pub fn translate(text: &str) -> String {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(text))
+        .cloned()
+        .unwrap_or_else(|| text.to_string())
+}
+
+pub fn translate_with(text: &str, args: &[&dyn Display]) -> String {
+    let template = translate(text);
+    let mut parts = template.split("{}");
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for (idx, part) in parts.enumerate() {
+        if let Some(arg) = args.get(idx) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
@@ -0,0 +1,57 @@
+//! A "needs attention" screen shown before the main menu: overdue tasks, tasks due today and
+//! active ones. Anything picked (or Escape) moves on to the main menu.
+//!
+//! ```toml
+//! [inbox]
+//! enabled = true
+//! limit = 10     # rows shown before "and N more"
+//! ```
+
+use crate::{
+    back_on_escape, format_task,
+    widgets::{pick, Row},
+    App,
+};
+use anyhow::Result;
+use chrono::{offset::Local as LocalTime, TimeZone};
+use task_hookrs::task::Task;
+
+/// `pending` is the startup query, shared with the other startup checks.
+pub fn show(app: &App, pending: &[Task]) -> Result<()> {
+    let config = &app.config.inbox;
+    if !config.enabled {
+        return Ok(());
+    }
+    let now = LocalTime::now();
+    // Taskwarrior exports dates in UTC
+    let due = |task: &Task| task.due().map(|due| LocalTime.from_utc_datetime(due));
+    let overdue = |task: &Task| due(task).is_some_and(|due| due < now);
+    let due_today = |task: &Task| due(task).is_some_and(|due| due.date() == now.date());
+
+    let attention: Vec<_> = pending
+        .iter()
+        .filter(|task| task.start().is_some() || overdue(task) || due_today(task))
+        .collect();
+    if attention.is_empty() {
+        return Ok(());
+    }
+
+    let mut rows: Vec<Row<()>> = attention
+        .iter()
+        .take(config.limit)
+        .map(|task| {
+            Row::new(format_task(task, &app.config.style), ())
+                .active(task.start().is_some())
+                .urgent(overdue(task))
+        })
+        .collect();
+    if attention.len() > config.limit {
+        rows.push(Row::header(
+            tr!("... and {} more", attention.len() - config.limit),
+            (),
+        ));
+    }
+    back_on_escape(
+        pick(&tr!("Needs attention (enter to continue)"), rows, false).map_err(Into::into),
+    )
+}
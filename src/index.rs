@@ -0,0 +1,49 @@
+//! Queried tasks looked up by UUID or working-set ID without scanning the list each time.
+
+use std::collections::HashMap;
+use task_hookrs::task::Task;
+
+pub struct TaskIndex {
+    tasks: Vec<Task>,
+    by_uuid: HashMap<String, usize>,
+    by_id: HashMap<u64, usize>,
+}
+
+impl TaskIndex {
+    pub fn new(tasks: Vec<Task>) -> Self {
+        let mut by_uuid = HashMap::with_capacity(tasks.len());
+        let mut by_id = HashMap::new();
+        for (idx, task) in tasks.iter().enumerate() {
+            by_uuid.insert(task.uuid().to_string(), idx);
+            if let Some(id) = task.id() {
+                by_id.insert(id, idx);
+            }
+        }
+        Self {
+            tasks,
+            by_uuid,
+            by_id,
+        }
+    }
+
+    /// The task with this UUID, or this ID when `key` is a number.
+    pub fn get(&self, key: &str) -> Option<&Task> {
+        self.position(key).map(|idx| &self.tasks[idx])
+    }
+
+    /// Where `get`'s task is in the order the tasks were given.
+    pub fn position(&self, key: &str) -> Option<usize> {
+        match key.parse::<u64>() {
+            Ok(id) => self.by_id.get(&id).copied(),
+            Err(_) => self.by_uuid.get(key).copied(),
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+}
@@ -0,0 +1,98 @@
+//! `taskwarrior-rofi init`: writes a commented starting config, filled in from what is installed,
+//! asking in the terminal about the optional integrations it finds.
+
+use crate::{get_config_var, paths, probe};
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
+
+pub fn init() -> Result<()> {
+    let path = paths::config_file()?;
+    if path.exists()
+        && !ask(
+            &tr!("{} already exists. Replace it?", path.display()),
+            false,
+        )?
+    {
+        bail!(tr!("Left {} as it was", path.display()));
+    }
+
+    let mut config = String::from(
+        "# taskwarrior-rofi settings. Everything is optional; delete what you don't need.\n\n",
+    );
+
+    match get_config_var("default.command") {
+        Ok(report) => config.push_str(&format!(
+            "# Task lists use the filter of your default report, `{}`.\n\n",
+            report
+        )),
+        Err(_) => config
+            .push_str("# Set default.command in taskrc: task lists use that report's filter.\n\n"),
+    }
+
+    config.push_str("# Features that need a UDA in taskrc:\n");
+    for (uda, feature, line) in probe::FEATURE_UDAS.iter() {
+        let state = if get_config_var(&format!("uda.{}.type", uda)).is_ok() {
+            tr!("ready")
+        } else {
+            tr!("add {}", line)
+        };
+        config.push_str(&format!("#   {}: {}\n", feature, state));
+    }
+    config.push('\n');
+
+    let auto_stop = ask(
+        &tr!("Offer to stop tasks left running for over 8 hours?"),
+        true,
+    )?;
+    if cfg!(feature = "timewarrior") && probe::on_path("timew") {
+        config.push_str("# timewarrior found: stopping can log the time actually worked.\n");
+    }
+    config.push_str(&format!(
+        "[auto_stop]\nenabled = {}\nafter = \"8h\"\n\n",
+        auto_stop
+    ));
+
+    if probe::on_path("bugwarrior-pull")
+        && ask(
+            &tr!(
+                "bugwarrior found. Show overdue and active tasks, like pulled issues, on startup?"
+            ),
+            true,
+        )?
+    {
+        config.push_str("[inbox]\nenabled = true\nlimit = 10\n\n");
+    } else {
+        config.push_str("# [inbox]\n# enabled = true\n# limit = 10\n\n");
+    }
+
+    config.push_str(
+        "[style]\n# ascii = true\n# emphasis = \"letters\"\n# columns = false\n\n\
+         [scheduler]\ndaily_capacity = 6.0\ndefault_estimate = 1.0\n\n\
+         # [sprint]\n# start = \"2026-01-05\"\n# length = 14\n\n\
+         # [notes]\n# dir = \"~/notes/tasks\"\n\n\
+         # [vault]\n# name = \"Work\"\n# path = \"~/vaults/work\"\n",
+    );
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, config).with_context(|| tr!("writing {}", path.display()))?;
+    println!("{}", tr!("Wrote {}", path.display()));
+    Ok(())
+}
+
+/// A yes/no question on the terminal, `default` on an empty answer.
+fn ask(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "" => default,
+        answer => answer.starts_with('y'),
+    })
+}
@@ -0,0 +1,613 @@
+//! The program that shows the menus. rofi is the one everything is built for; dmenu, wofi,
+//! fuzzel and bemenu work the same way from a hotkey, and `choose`, `fzf` and a numbered list in
+//! the terminal cover systems without any of them.
+//!
+//! `launcher = "wofi"` in the config (or `TASKWARRIOR_ROFI_LAUNCHER`, which wins) picks one by
+//! name. Otherwise rofi is used when installed, then on Wayland fuzzel, wofi or bemenu, then
+//! dmenu, `choose` on macOS and `fzf`; failing those the terminal, except on Linux where rofi is
+//! assumed so a missing one gets rofi's error.
+//!
+//! Only rofi has everything:
+//!
+//! | launcher   | markup | icons | custom keys | message pane | free text |
+//! |------------|--------|-------|-------------|--------------|-----------|
+//! | `rofi`     | yes    | yes   | yes         | yes          | yes       |
+//! | `wofi`     | yes    | no    | no          | no           | yes       |
+//! | `fuzzel`   | no     | yes   | no          | no           | yes       |
+//! | `dmenu`    | no     | no    | no          | no           | yes       |
+//! | `bemenu`   | no     | no    | no          | no           | yes       |
+//! | `choose`   | no     | no    | no          | no           | no        |
+//! | `fzf`      | no     | no    | no          | no           | yes       |
+//! | `terminal` | no     | no    | no          | no           | yes       |
+//!
+//! What a launcher can't show is left out: markup is reduced to its text, icons, custom keys and
+//! the message pane are dropped. `choose` has no free text, so prompts for it use a dialog.
+//! dmenu, wofi and bemenu answer with the row's text instead of its position, so rows repeating
+//! an earlier one get a count like `(2)`.
+
+use crate::probe::on_path;
+use anyhow::{bail, Result};
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, BufRead, Read, Write},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+static LAUNCHER: OnceLock<Box<dyn Launcher>> = OnceLock::new();
+
+pub struct Capabilities {
+    pub markup: bool,
+    pub icons: bool,
+    pub keys: bool,
+    pub message: bool,
+}
+
+/// A picker as the widgets ask for it. Labels are rendered rows, with rofi's row options and,
+/// when `markup` is set, pango markup.
+pub struct Pick<'a> {
+    pub prompt: &'a str,
+    pub labels: &'a [String],
+    pub markup: bool,
+    /// rofi's custom keybindings, as `(N, key)` for `-kb-custom-N`.
+    pub keys: &'a [(u8, String)],
+    pub message: Option<&'a str>,
+    pub selected: usize,
+    pub tab_stops: Option<&'a [String]>,
+}
+
+pub trait Launcher: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// The index of the chosen row and the custom key it was chosen with. Escape is
+    /// `rofi::Error::Interrupted`.
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error>;
+
+    /// Free text, or one of the suggestions.
+    fn text(&self, prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error>;
+}
+
+/// Sets the launcher, by its name in the config unless the environment names one.
+pub fn init(name: Option<&str>) -> Result<()> {
+    let from_env = env::var("TASKWARRIOR_ROFI_LAUNCHER").ok();
+    let launcher = match from_env.as_deref().or(name) {
+        Some(name) => match by_name(name) {
+            Some(launcher) => launcher,
+            None => bail!(tr!("unknown launcher `{}`", name)),
+        },
+        None => detect(),
+    };
+    let _ = LAUNCHER.set(launcher);
+    Ok(())
+}
+
+/// The launcher `init` set, or the one found on PATH.
+pub fn current() -> &'static dyn Launcher {
+    LAUNCHER
+        .get_or_init(|| {
+            env::var("TASKWARRIOR_ROFI_LAUNCHER")
+                .ok()
+                .and_then(|name| by_name(&name))
+                .unwrap_or_else(detect)
+        })
+        .as_ref()
+}
+
+fn by_name(name: &str) -> Option<Box<dyn Launcher>> {
+    Some(match name {
+        "rofi" => Box::new(Rofi),
+        "dmenu" => Box::new(DmenuStyle::Dmenu),
+        "wofi" => Box::new(DmenuStyle::Wofi),
+        "fuzzel" => Box::new(DmenuStyle::Fuzzel),
+        "bemenu" => Box::new(DmenuStyle::Bemenu),
+        "choose" => Box::new(Choose),
+        "fzf" => Box::new(Fzf),
+        "terminal" => Box::new(Terminal),
+        _ => return None,
+    })
+}
+
+fn detect() -> Box<dyn Launcher> {
+    let mut candidates = vec!["rofi"];
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        candidates.extend(["fuzzel", "wofi", "bemenu"]);
+    }
+    candidates.push("dmenu");
+    if cfg!(target_os = "macos") {
+        candidates.push("choose");
+    }
+    candidates.push("fzf");
+    let found = candidates.into_iter().find(|name| on_path(name));
+    match found {
+        Some(name) => by_name(name).expect("candidates are launcher names"),
+        None if cfg!(target_os = "linux") => Box::new(Rofi),
+        None => Box::new(Terminal),
+    }
+}
+
+struct Rofi;
+
+impl Launcher for Rofi {
+    fn name(&self) -> &'static str {
+        "rofi"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            markup: true,
+            icons: true,
+            keys: true,
+            message: true,
+        }
+    }
+
+    /// `rofi -dmenu` with the arguments the rofi crate has no method for.
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error> {
+        let mut args = vec![];
+        if let Some(message) = pick.message {
+            args.push("-mesg".to_string());
+            args.push(message.to_string());
+        }
+        if pick.selected > 0 {
+            args.push("-selected-row".to_string());
+            args.push(pick.selected.to_string());
+        }
+        for (number, key) in pick.keys {
+            args.push(format!("-kb-custom-{}", number));
+            args.push(key.clone());
+        }
+        if pick.markup {
+            args.push("-markup-rows".to_string());
+        }
+        let tabbed = pick.labels.iter().any(|label| label.contains('\t'));
+        if let Some(stops) = pick.tab_stops.filter(|_| tabbed) {
+            args.push("-theme-str".to_string());
+            args.push(format!(
+                "element-text {{ tab-stops: [{}]; }}",
+                stops.join(", ")
+            ));
+        }
+
+        let mut child = Command::new("rofi")
+            .args(["-dmenu", "-i", "-format", "i", "-p", pick.prompt])
+            .arg("-lines")
+            .arg(pick.labels.len().to_string())
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            for label in pick.labels {
+                writeln!(stdin, "{}", label)?;
+            }
+        }
+
+        // Custom keys 1 to 19 exit with 10 to 28
+        let key = match child.wait()?.code() {
+            Some(0) => None,
+            Some(code @ 10..=28) => Some(code as u8 - 9),
+            _ => return Err(rofi::Error::Interrupted),
+        };
+        let mut output = String::new();
+        if let Some(mut stdout) = child.stdout.take() {
+            stdout.read_to_string(&mut output)?;
+        }
+        Ok((index(&output, pick.labels.len())?, key))
+    }
+
+    fn text(&self, prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error> {
+        rofi::Rofi::new(&suggestions.to_vec()).prompt(prompt).run()
+    }
+}
+
+/// The dmenu family: rows on stdin, the chosen one (or the typed text) on stdout.
+enum DmenuStyle {
+    Dmenu,
+    Wofi,
+    Fuzzel,
+    Bemenu,
+}
+
+impl DmenuStyle {
+    fn command(&self, prompt: &str, lines: usize, markup: bool) -> Command {
+        let lines = lines.clamp(1, 20).to_string();
+        let mut command = Command::new(self.name());
+        match self {
+            DmenuStyle::Dmenu | DmenuStyle::Bemenu => {
+                command.args(["-i", "-p", prompt, "-l", &lines]);
+            }
+            DmenuStyle::Wofi => {
+                command.args(["--dmenu", "-i", "-p", prompt, "-L", &lines]);
+                if markup {
+                    command.arg("--allow-markup");
+                }
+            }
+            DmenuStyle::Fuzzel => {
+                command
+                    .args(["--dmenu", "-l", &lines, "-p"])
+                    .arg(format!("{}: ", prompt));
+            }
+        }
+        command
+    }
+}
+
+impl Launcher for DmenuStyle {
+    fn name(&self) -> &'static str {
+        match self {
+            DmenuStyle::Dmenu => "dmenu",
+            DmenuStyle::Wofi => "wofi",
+            DmenuStyle::Fuzzel => "fuzzel",
+            DmenuStyle::Bemenu => "bemenu",
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            markup: matches!(self, DmenuStyle::Wofi),
+            icons: matches!(self, DmenuStyle::Fuzzel),
+            keys: false,
+            message: false,
+        }
+    }
+
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error> {
+        let labels = plain_labels(pick, &self.capabilities());
+        let mut command = self.command(pick.prompt, labels.len(), pick.markup);
+        if let DmenuStyle::Fuzzel = self {
+            command.arg("--index");
+            let output = run(&mut command, &labels, &[])?;
+            return Ok((index(&output, labels.len())?, None));
+        }
+        // The others answer with the row itself, which is looked up again
+        let labels = distinct(labels);
+        let output = run(&mut command, &labels, &[])?;
+        let chosen = output.trim_end_matches('\n');
+        let mut matches = labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| *label == chosen)
+            .map(|(idx, _)| idx);
+        match (matches.next(), matches.next()) {
+            (Some(idx), None) => Ok((idx, None)),
+            (None, _) if chosen.is_empty() => Err(rofi::Error::Blank),
+            // Typed text, or a row that still can't be told from another
+            _ => Err(rofi::Error::NotFound),
+        }
+    }
+
+    fn text(&self, prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error> {
+        let mut command = self.command(prompt, suggestions.len(), false);
+        non_blank(&run(&mut command, suggestions, &[])?)
+    }
+}
+
+/// `choose` on macOS, which can only pick.
+struct Choose;
+
+impl Launcher for Choose {
+    fn name(&self) -> &'static str {
+        "choose"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            markup: false,
+            icons: false,
+            keys: false,
+            message: false,
+        }
+    }
+
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error> {
+        let labels = plain_labels(pick, &self.capabilities());
+        let output = run(
+            Command::new("choose").args(["-i", "-p", pick.prompt]),
+            &labels,
+            &[],
+        )?;
+        Ok((index(&output, labels.len())?, None))
+    }
+
+    /// macOS's own text prompt.
+    fn text(&self, prompt: &str, _suggestions: &[String]) -> Result<String, rofi::Error> {
+        let script = format!(
+            "text returned of (display dialog \"{}\" default answer \"\")",
+            prompt.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        let output = Command::new("osascript").args(["-e", &script]).output()?;
+        if !output.status.success() {
+            return Err(rofi::Error::Interrupted);
+        }
+        non_blank(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+struct Fzf;
+
+impl Launcher for Fzf {
+    fn name(&self) -> &'static str {
+        "fzf"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            markup: false,
+            icons: false,
+            keys: false,
+            message: false,
+        }
+    }
+
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error> {
+        // Each line carries its index in a hidden first field
+        let lines: Vec<_> = plain_labels(pick, &self.capabilities())
+            .iter()
+            .enumerate()
+            .map(|(idx, label)| format!("{}\t{}", idx, label))
+            .collect();
+        let output = run(
+            Command::new("fzf")
+                .args(["--delimiter", "\t", "--with-nth", "2..", "--prompt"])
+                .arg(format!("{}> ", pick.prompt)),
+            &lines,
+            &[],
+        )?;
+        let idx = output.split('\t').next().unwrap_or_default();
+        Ok((index(idx, lines.len())?, None))
+    }
+
+    fn text(&self, prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error> {
+        let output = run(
+            Command::new("fzf")
+                .args(["--print-query", "--prompt"])
+                .arg(format!("{}> ", prompt)),
+            suggestions,
+            // fzf exits with 1 when nothing matched the query
+            &[1],
+        )?;
+        // The query, then the match if there was one
+        let mut lines = output.lines();
+        let query = lines.next().unwrap_or_default();
+        non_blank(lines.next().unwrap_or(query))
+    }
+}
+
+/// A numbered list on stderr, answered on stdin.
+struct Terminal;
+
+impl Launcher for Terminal {
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            markup: false,
+            icons: false,
+            keys: false,
+            message: false,
+        }
+    }
+
+    fn pick(&self, pick: &Pick) -> Result<(usize, Option<u8>), rofi::Error> {
+        let labels = plain_labels(pick, &self.capabilities());
+        let mut stderr = io::stderr();
+        for (idx, label) in labels.iter().enumerate() {
+            writeln!(stderr, "{:>3}  {}", idx + 1, label)?;
+        }
+        let line = read_line(pick.prompt)?;
+        match line.parse::<usize>() {
+            Ok(number) if (1..=labels.len()).contains(&number) => Ok((number - 1, None)),
+            _ if line.is_empty() => Err(rofi::Error::Blank),
+            _ => Err(rofi::Error::NotFound),
+        }
+    }
+
+    fn text(&self, prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error> {
+        if !suggestions.is_empty() {
+            eprintln!("{}", suggestions.join(", "));
+        }
+        non_blank(&read_line(prompt)?)
+    }
+}
+
+/// A printed row index, checked against the number of rows.
+fn index(output: &str, rows: usize) -> Result<usize, rofi::Error> {
+    match output.trim() {
+        "" => Err(rofi::Error::Blank),
+        idx => match idx.parse::<isize>()? {
+            idx if idx < 0 || idx as usize >= rows => Err(rofi::Error::NotFound),
+            idx => Ok(idx as usize),
+        },
+    }
+}
+
+fn non_blank(text: &str) -> Result<String, rofi::Error> {
+    match text.trim() {
+        "" => Err(rofi::Error::Blank),
+        text => Ok(text.to_string()),
+    }
+}
+
+/// Writes `lines` to the command and returns what it printed. Escape, or an exit with a code
+/// other than 0 or those in `accept`, comes back as `Interrupted`.
+fn run(command: &mut Command, lines: &[String], accept: &[i32]) -> Result<String, rofi::Error> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            writeln!(stdin, "{}", line)?;
+        }
+    }
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout.read_to_string(&mut output)?;
+    }
+    match child.wait()?.code() {
+        Some(0) => Ok(output),
+        Some(code) if accept.contains(&code) => Ok(output),
+        _ => Err(rofi::Error::Interrupted),
+    }
+}
+
+/// A line from the terminal; end of input is Escape.
+fn read_line(prompt: &str) -> Result<String, rofi::Error> {
+    eprint!("{}: ", prompt);
+    io::stderr().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(rofi::Error::Interrupted);
+    }
+    Ok(line.trim().to_string())
+}
+
+fn plain_labels(pick: &Pick, can: &Capabilities) -> Vec<String> {
+    pick.labels
+        .iter()
+        .map(|label| plain(label, pick.markup, can))
+        .collect()
+}
+
+/// Labels told apart by a count after repeats, like `Call mom (2)`, for launchers that answer
+/// with the row's text rather than its index.
+fn distinct(labels: Vec<String>) -> Vec<String> {
+    let mut seen = HashMap::new();
+    labels
+        .into_iter()
+        .map(|label| {
+            let count = seen.entry(label.clone()).or_insert(0);
+            *count += 1;
+            match *count {
+                1 => label,
+                count => format!("{} ({})", label, count),
+            }
+        })
+        .collect()
+}
+
+/// A row's label for a launcher with the given capabilities: of rofi's row options only the icon,
+/// and only when it shows icons, and with `markup` reduced to its text when it can't show it.
+fn plain(label: &str, markup: bool, can: &Capabilities) -> String {
+    let (label, options) = label.split_once('\0').unwrap_or((label, ""));
+    // Options are `key\x1fvalue` pairs, also separated by `\x1f`
+    let fields: Vec<_> = options.split('\x1f').collect();
+    let icon = fields
+        .chunks(2)
+        .find(|pair| pair[0] == "icon")
+        .and_then(|pair| pair.get(1))
+        .filter(|_| can.icons);
+    let mut text = if !markup || can.markup {
+        label.to_string()
+    } else {
+        let mut text = String::with_capacity(label.len());
+        let mut in_tag = false;
+        for c in label.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                c if !in_tag => text.push(c),
+                _ => {}
+            }
+        }
+        text.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&")
+    };
+    if let Some(icon) = icon {
+        text.push_str(&format!("\0icon\x1f{}", icon));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BARE: Capabilities = Capabilities {
+        markup: false,
+        icons: false,
+        keys: false,
+        message: false,
+    };
+
+    const ICONS: Capabilities = Capabilities {
+        markup: false,
+        icons: true,
+        keys: false,
+        message: false,
+    };
+
+    const MARKUP: Capabilities = Capabilities {
+        markup: true,
+        icons: false,
+        keys: false,
+        message: false,
+    };
+
+    #[test]
+    fn index_in_range() {
+        assert_eq!(index("2\n", 3).unwrap(), 2);
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        assert!(matches!(index("3", 3), Err(rofi::Error::NotFound)));
+        assert!(matches!(index("-1", 3), Err(rofi::Error::NotFound)));
+    }
+
+    #[test]
+    fn index_blank() {
+        assert!(matches!(index(" \n", 3), Err(rofi::Error::Blank)));
+    }
+
+    #[test]
+    fn plain_strips_markup() {
+        assert_eq!(
+            plain("<b>Pay</b> rent &amp; bills &lt;3", true, &BARE),
+            "Pay rent & bills <3"
+        );
+    }
+
+    #[test]
+    fn plain_keeps_markup_the_launcher_shows() {
+        assert_eq!(plain("<b>Pay</b>", true, &MARKUP), "<b>Pay</b>");
+    }
+
+    #[test]
+    fn plain_leaves_text_that_isnt_markup() {
+        assert_eq!(plain("a <b> c", false, &BARE), "a <b> c");
+    }
+
+    #[test]
+    fn plain_drops_row_options_without_icons() {
+        assert_eq!(
+            plain("Call\0icon\x1fphone\x1furgent\x1ftrue", false, &BARE),
+            "Call"
+        );
+    }
+
+    #[test]
+    fn plain_keeps_only_the_icon() {
+        assert_eq!(
+            plain("Call\0urgent\x1ftrue\x1ficon\x1fphone", false, &ICONS),
+            "Call\0icon\x1fphone"
+        );
+        assert_eq!(
+            plain("Today\0nonselectable\x1ftrue", false, &ICONS),
+            "Today"
+        );
+    }
+
+    #[test]
+    fn distinct_counts_repeats() {
+        let labels = ["a", "b", "a", "a"].map(String::from).to_vec();
+        assert_eq!(distinct(labels), ["a", "b", "a (2)", "a (3)"]);
+    }
+}
@@ -0,0 +1,50 @@
+//! Errands split by place: tasks carry a `location` UDA, the current location is kept in state,
+//! and Here shows only the tasks for where you are.
+
+use crate::{get_config_var, query_sorted, quick, widgets, App, TaskExt};
+use anyhow::{bail, Result};
+use std::collections::BTreeSet;
+
+const UDA: &str = "location";
+
+pub fn set_location(app: &mut App) -> Result<()> {
+    let clear = tr!("Nowhere in particular");
+    // Locations already used on tasks, so they don't have to be typed exactly right
+    let known: BTreeSet<String> = query_sorted("status:pending")?
+        .iter()
+        .filter_map(|task| task.uda_string(UDA))
+        .collect();
+    let mut suggestions: Vec<_> = known.into_iter().collect();
+    suggestions.push(clear.clone());
+
+    let prompt = match &app.state.location {
+        Some(location) => tr!("Where are you? (now {})", location),
+        None => tr!("Where are you?"),
+    };
+    let input = widgets::text_with(&prompt, &suggestions)?;
+    let input = input.trim();
+    app.state.location = if input.is_empty() || input == clear {
+        None
+    } else {
+        Some(input.to_string())
+    };
+    app.state.save()
+}
+
+pub fn here(app: &App) -> Result<()> {
+    let location = match &app.state.location {
+        Some(location) => location,
+        None => bail!(tr!("No location set, use Set location first")),
+    };
+    if get_config_var(&format!("uda.{}.type", UDA)).is_err() {
+        bail!(tr!(
+            "Define the location UDA in taskrc first: uda.location.type=string"
+        ));
+    }
+
+    let tasks = query_sorted(&format!("status:pending {}.is:{}", UDA, location))?;
+    if tasks.is_empty() {
+        bail!(tr!("Nothing to do at {}", location));
+    }
+    quick::pick_and_act(&tr!("Here ({})", location), tasks, &app.config.style)
+}
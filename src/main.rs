@@ -1,209 +1,1020 @@
+#[macro_use]
+mod i18n;
+
+mod age;
+mod auto_stop;
+mod backup;
+mod balance;
+mod board;
+mod branch;
+mod cache;
+mod call;
+mod checklist;
+mod cli;
+mod colors;
+mod config;
+mod conflict;
+mod context;
+mod copy;
+mod date_picker;
+mod dates;
+#[cfg(feature = "dbus")]
+mod dbus;
+mod duration;
+mod energy;
+mod export;
+mod flow;
+mod focus;
+mod grammar;
+mod graph;
+mod inbox;
+mod index;
+mod init;
+mod launcher;
+mod location;
+mod menu;
+mod merge;
+mod modify;
+mod mutation;
+mod native;
+mod notes;
+mod paths;
+mod planner;
+mod platform;
+mod plugins;
+mod privacy;
+mod probe;
+mod projects;
+mod quick;
+mod recurrence;
+mod related;
+#[cfg(feature = "notifications")]
+mod remind;
+mod reports;
+mod reschedule;
+mod resume;
+mod roulette;
+mod schedule;
+mod scripting;
+#[cfg(feature = "search-provider")]
+mod search;
+#[cfg(feature = "http-api")]
+mod server;
+mod session;
+mod share;
+mod someday;
+mod split;
+mod stalled;
+mod state;
+mod style;
+mod subtask;
+mod sync;
+mod theme;
+mod today;
+mod urgency;
+mod vault;
+mod watch;
+mod webhook;
+mod weeks;
+mod widgets;
+mod workload;
+
 use anyhow::{anyhow, bail, Context, Result};
-use chrono::{offset::Local as LocalTime, NaiveDateTime};
-use rofi::Rofi;
+use chrono::{offset::Local as LocalTime, Duration, NaiveDateTime, TimeZone};
+use config::{Config, SortOrder};
+use flow::Flow;
+use graph::DependencyGraph;
+use plugins::Plugin;
+use schedule::Profile;
+use scripting::Scripts;
+use state::{LastAdd, State};
 use std::{
-    fmt::Display,
+    cell::RefCell,
+    cmp::Ordering,
+    env,
+    fmt::Write,
+    path::Path,
     process::{Command, Stdio},
 };
-use task_hookrs::{
-    annotation::Annotation, date::Date as TwDate, status::TaskStatus, task::Task, tw,
-};
+use style::{Glyph, Style};
+use task_hookrs::{annotation::Annotation, status::TaskStatus, task::Task, uda::UDAValue};
+use widgets::{rich_rofi, Row};
 
 fn main() {
-    match ui() {
+    // Browsers start native messaging hosts with arguments of their own
+    if env::args()
+        .nth(1)
+        .is_some_and(|arg| native::started_by_browser(&arg))
+    {
+        if let Err(err) = native::host() {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let matches = cli::command().get_matches();
+    cli::init(&matches);
+    let jump = match matches.subcommand() {
+        Some(("status", _)) => {
+            match focus::status_line() {
+                Ok(line) => println!("{}", line),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(("doctor", _)) => {
+            if !probe::doctor() {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("init", _)) => {
+            if let Err(err) = init::init() {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("completions", args)) => {
+            let shell = args
+                .get_one::<clap_complete::Shell>("shell")
+                .expect("shell is required");
+            print!("{}", cli::completions(*shell));
+            return;
+        }
+        Some(("theme", _)) => {
+            match Config::load() {
+                Ok(config) => print!("{}", theme::export(&config.style)),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(("manpage", _)) => {
+            match cli::manpage() {
+                Ok(page) => print!("{}", page),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        #[cfg(feature = "notifications")]
+        Some(("remind", _)) => {
+            if let Err(err) = remind::remind() {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("warm", _)) => {
+            if let Err(err) = cache::warm() {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("review-old", _)) => {
+            show_errors(App::load().and_then(|mut app| stalled::review_old(&mut app)));
+            return;
+        }
+        #[cfg(feature = "http-api")]
+        Some(("serve", _)) => {
+            if let Err(err) = server::serve() {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("menu", args)) => {
+            let args: Vec<_> = args
+                .get_many::<String>("args")
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            match menu::menu(&args) {
+                Ok(code) => std::process::exit(code),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(("ping", args)) => {
+            let request: Vec<_> = args
+                .get_many::<String>("request")
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            match watch::ping(&request.join(" ")) {
+                Ok(reply) => print!("{}", reply),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        // An action, built in or from a plugin or script
+        Some((action, _)) => Some(action),
+        None => None,
+    };
+
+    if matches.get_flag("watch") {
+        let watcher = watch::Watcher::start().unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        });
+        let mut wake = watch::Wake::Menu;
+        loop {
+            match wake {
+                watch::Wake::Menu => run_menu(jump),
+                #[cfg(feature = "search-provider")]
+                watch::Wake::Task(uuid) => show_errors(
+                    Config::load().and_then(|config| search::activate(&uuid, &config.style)),
+                ),
+            }
+            wake = watcher.wait().unwrap_or_else(|err| {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            });
+        }
+    }
+    run_menu(jump);
+}
+
+/// Runs the menu until it closes, showing any error in rofi. An action named on the command line,
+/// like `taskwarrior-rofi done`, runs once without the menu.
+fn run_menu(jump: Option<&str>) {
+    show_errors(ui(jump));
+}
+
+fn show_errors(result: Result<()>) {
+    match result {
         Ok(_) => {}
         Err(err) => match err.downcast_ref::<rofi::Error>() {
             Some(rofi::Error::Interrupted) => (),
             Some(_) | None => {
-                Rofi::new(&vec![format!("Error: {}", err)])
-                    .run()
+                widgets::message(&tr!("Error: {}", err))
                     .expect("Couldn't even use rofi to show an error");
             }
         },
     }
 }
 
-fn ui() -> Result<()> {
+/// Everything loaded once at startup and shared by the actions.
+struct App {
+    config: Config,
+    plugins: Vec<Plugin>,
+    scripts: Scripts,
+    state: State,
+    profile: Option<Profile>,
+    /// From `--filter`, used in place of the default report.
+    filter: Option<String>,
+}
+
+impl App {
+    fn load() -> Result<Self> {
+        let mut config = Config::load().context(tr!("loading config"))?;
+        config.read_only |= cli::read_only();
+        i18n::init(config.locale.as_deref()).context(tr!("loading translations"))?;
+        if config.style.markup() {
+            config.style.task_colors =
+                colors::TaskColors::load().context(tr!("loading taskwarrior colors"))?;
+        }
+        launcher::init(config.launcher.as_deref())?;
+        widgets::init(&config.style);
+        weeks::init(&config)?;
+        grammar::init(&config.add.grammar).context(tr!("checking add.grammar"))?;
+        webhook::init(&config.webhook);
+        privacy::init(&config.privacy);
+        quick::init(config.read_only);
+        age::init(&config.age);
+        let profile = schedule::active_profile(&config.profiles, LocalTime::now().naive_local())?;
+        let filter = cli::filter().map(str::to_string);
+        Ok(Self {
+            plugins: Plugin::discover().context(tr!("loading plugins"))?,
+            scripts: Scripts::load().context(tr!("loading scripts"))?,
+            state: State::load().context(tr!("loading state"))?,
+            config,
+            profile,
+            filter,
+        })
+    }
+
+    fn task_prompt(&self) -> String {
+        match &self.config.menu.prompts.task {
+            Some(prompt) => prompt.clone(),
+            None => tr!("Choose a task"),
+        }
+    }
+
+    /// The configured wait presets, or the usual ones.
+    fn wait_presets(&self) -> Vec<&str> {
+        match self.config.menu.wait_presets.as_slice() {
+            [] => dates::WAIT_PRESETS.to_vec(),
+            presets => presets.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+fn ui(jump: Option<&str>) -> Result<()> {
+    probe::check_startup()?;
+    let mut app = App::load()?;
+    let mut jump = match jump {
+        Some(name) => Some(Action::named(&app, name)?),
+        None => None,
+    };
+    let one_shot = jump.is_some();
+    // A named action is for getting straight to it, so the startup checks wait for the menu
+    if !one_shot {
+        // One query for all the startup checks, so they don't each add latency
+        let mut pending = query_sorted("status:pending")?;
+        // Stopping forgotten tasks makes changes
+        if !app.config.read_only && auto_stop::check(&app, &pending)? {
+            pending = query_sorted("status:pending")?;
+        }
+        inbox::show(&app, &pending)?;
+    }
+
     loop {
-        let actions = Action::all();
-        let action = rich_rofi("Choose an action", actions)?;
+        // The named action has had its turn
+        if one_shot && jump.is_none() {
+            return Ok(());
+        }
+        let focused = focus::focused_task(&app.state)?;
+        let prompt = match (&focused, &app.profile, &app.config.menu.prompts.actions) {
+            (Some(task), _, _) => tr!("Focus: {}", task.description()),
+            (None, _, Some(prompt)) => prompt.clone(),
+            (None, Some(profile), None) => tr!("Choose an action ({})", profile.name),
+            (None, None, None) => tr!("Choose an action"),
+        };
+        let actions = Action::all(&app, focused.is_some());
+        // Escaping the main menu is the same as Exit
+        let action = match jump.take() {
+            Some(action) => action,
+            None => match rich_rofi(&prompt, actions) {
+                Err(rofi::Error::Interrupted) => Action::Exit,
+                action => action?,
+            },
+        };
 
         match action {
             Action::Add => {
-                let (task_text, annotations) = {
-                    let input = Rofi::<String>::new(&vec![])
-                        .prompt("task -- annotation")
-                        .run()?;
-                    let mut parts = input.split("--");
-                    (
-                        parts
-                            .next()
-                            .ok_or_else(|| anyhow!("No input given to add"))?
-                            .to_string(),
-                        parts.map(|ann| ann.trim().to_string()).collect::<Vec<_>>(),
-                    )
+                let write_context = context::write_context()?;
+                let base = match &app.config.menu.prompts.add {
+                    Some(prompt) => prompt.clone(),
+                    None => grammar::hint(),
+                };
+                let prompt = match &write_context {
+                    Some(ctx) => format!("{} [{}: {}]", base, ctx.name, ctx.defaults),
+                    None => base,
+                };
+                let input = widgets::text_with(&prompt, &app.state.history("add"))?;
+                app.state.remember("add", &input)?;
+                let (task_text, annotations) = split_add_input(&input)?;
+
+                let use_context = match write_context {
+                    Some(ctx) => rich_rofi(
+                        &tr!("Context defaults"),
+                        vec![
+                            Row::new(
+                                tr!("Add with {} defaults: {}", ctx.name, ctx.defaults),
+                                true,
+                            ),
+                            Row::new(tr!("Add without context defaults"), false),
+                        ],
+                    )?,
+                    None => true,
                 };
 
-                add_task(task_text, annotations)?;
+                if is_double_entry(&input, &app)?
+                    && !widgets::confirm(
+                        &tr!("You just added this. Add it again?"),
+                        &tr!("Add again"),
+                        &tr!("Cancel"),
+                        vec![input.clone()],
+                    )?
+                {
+                    continue;
+                }
+                if !recurrence::confirm(task_text.split_whitespace(), None)? {
+                    continue;
+                }
+                add_task(task_text, annotations, use_context)?;
+                app.state.last_add = Some(LastAdd {
+                    text: input.trim().to_string(),
+                    at: LocalTime::now().timestamp(),
+                });
+                app.state.save()?;
             }
 
             Action::List => {
-                match task_rofi("Press enter to go back") {
-                    Ok(_) => Ok(()),
-                    Err(e) => match e.downcast_ref::<rofi::Error>() {
-                        Some(rofi::Error::Interrupted) => Ok(()),
-                        _ => Err(e),
-                    },
-                }?;
+                back_on_escape(task_rofi(&tr!("Press enter to go back"), &app).map(|_| ()))?
             }
 
             Action::Mod => {
-                let mut task = task_rofi("Choose a task")?;
-                mod_task(&mut task)?
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                modify::mod_task(&task, &mut app)?
+            }
+
+            Action::Wait => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                let date = dates::pick_date(&tr!("Wait until?"), &app.wait_presets(), &app.config)?;
+                task_command(vec![
+                    &task.uuid().to_string(),
+                    "mod",
+                    &format!("wait:{}", date),
+                ])
+                .context(tr!("modifying wait"))?;
+            }
+
+            Action::Estimate => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                if get_config_var("uda.estimate.type").is_err() {
+                    bail!(tr!(
+                        "Define the estimate UDA in taskrc first: uda.estimate.type=string"
+                    ));
+                }
+                let estimate =
+                    duration::pick_duration(&tr!("Estimate"), &duration::ESTIMATE_PRESETS)?;
+                task_command(vec![
+                    &task.uuid().to_string(),
+                    "mod",
+                    &format!("estimate:{}", duration::format(estimate)),
+                ])
+                .context(tr!("modifying estimate"))?;
+            }
+
+            Action::Someday => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                someday::park(&task)?;
+            }
+
+            Action::Energy => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                energy::set_energy(&task)?;
+            }
+
+            Action::Schedule => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                let date =
+                    dates::pick_date(&tr!("Schedule for?"), &dates::SCHEDULE_PRESETS, &app.config)?;
+                task_command(vec![
+                    &task.uuid().to_string(),
+                    "mod",
+                    &format!("scheduled:{}", date),
+                ])
+                .context(tr!("modifying scheduled"))?;
+            }
+
+            Action::Unwait => {
+                let rows: Vec<_> = query_sorted("+WAITING")?
+                    .into_iter()
+                    .map(|task| Row::new(format_task(&task, &app.config.style), task))
+                    .collect();
+                if rows.is_empty() {
+                    bail!(tr!("No tasks are waiting"));
+                }
+                let task: Task = rich_rofi(&tr!("Stop waiting on"), rows)?;
+                task_command(vec![&task.uuid().to_string(), "mod", "wait:"])
+                    .context(tr!("modifying wait"))?;
+            }
+
+            Action::Due => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                let date = dates::pick_date(&tr!("Due when?"), &dates::DUE_PRESETS, &app.config)?;
+                let date = dates::pick_time(&date)?;
+                task_command(vec![
+                    &task.uuid().to_string(),
+                    "mod",
+                    &format!("due:{}", date),
+                ])
+                .context(tr!("modifying due"))?;
             }
 
-            Action::Exit => return Ok(()),
+            Action::Plan => back_on_escape(planner::plan(&app))?,
 
-            _ => {
-                let mut task = task_rofi("Choose a task")?;
+            Action::RescheduleOverdue => {
+                back_on_escape(reschedule::reschedule_overdue(&app.config.style))?
+            }
+
+            Action::SlipProject => back_on_escape(reschedule::slip_project(&app))?,
+
+            Action::Board => back_on_escape(board::board(&app))?,
+
+            Action::Workload => back_on_escape(workload::workload(&app))?,
+            Action::Balance => back_on_escape(balance::balance(&app))?,
+            Action::ProjectStatus => back_on_escape(projects::project_status(&app))?,
+            Action::Stalled => back_on_escape(stalled::stalled(&mut app))?,
+            Action::DependencyGraph => back_on_escape(graph::export())?,
+
+            Action::Today => back_on_escape(today::today(&mut app))?,
+            Action::Here => back_on_escape(location::here(&app))?,
+            Action::SetLocation => location::set_location(&mut app)?,
+            Action::WhatNow => back_on_escape(energy::what_now(&app))?,
+            Action::SomedayList => back_on_escape(someday::browse(app.config.read_only))?,
+            Action::NextList => {
+                let tasks = query_sorted("status:pending +next")?;
+                if tasks.is_empty() {
+                    bail!(tr!("No tasks are tagged +next"));
+                }
+                back_on_escape(quick::pick_and_act(&tr!("Next"), tasks, &app.config.style))?;
+            }
+            Action::Report => back_on_escape(reports::report(&app))?,
+            Action::Context => back_on_escape(context::switch())?,
+
+            Action::Subtask => {
+                let parent = task_rofi(&tr!("Choose a parent task"), &app)?;
+                subtask::add_subtasks(&parent)?;
+            }
+
+            Action::Split => {
+                let task = task_rofi(&tr!("Choose a task to split"), &app)?;
+                split::split(&task)?;
+            }
+
+            Action::Merge => {
+                let keep = task_rofi(&tr!("Choose the task to keep"), &app)?;
+                let other = task_rofi(&tr!("Choose the duplicate to merge into it"), &app)?;
+                merge::merge(keep, other, &app.config.style)?;
+            }
+
+            Action::Copy => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                copy::copy_task(&task)?;
+            }
+
+            Action::Branch => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                branch::branch_for_task(&task, &app.config.branch)?;
+            }
+
+            Action::Share => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                share::share(&task, &app.config.share)?;
+            }
+
+            Action::Focus => {
+                let task = task_rofi(&tr!("Choose a task to focus on"), &app)?;
+                focus::focus(task, &mut app.state)?;
+            }
+
+            Action::FocusDone => {
+                let task = focused.expect("only offered while focused");
+                focus::done(task, &mut app.state)?;
+            }
+
+            Action::Unfocus => {
+                let task = focused.expect("only offered while focused");
+                focus::unfocus(task, &mut app.state)?;
+            }
+
+            Action::Roulette => roulette::roulette(&mut app)?,
+            Action::Resume => resume::resume(&mut app)?,
+
+            Action::Plugin(plugin) => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                plugin.run(&task)?;
+            }
+
+            Action::Script(name) => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                app.scripts.run_action(&name, &task)?;
+            }
+
+            Action::Annotate => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                let mut input = widgets::text(&tr!("annotation"))?;
+                if app.config.age.annotations {
+                    input = privacy::encrypt(&input, &app.config.privacy)?;
+                }
+                mutation::annotate(&task.uuid().to_string(), &input)?;
+            }
+
+            Action::Denotate => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                let annotations = task
+                    .annotations()
+                    .into_iter()
+                    .flatten()
+                    .map(|ann| Row::new(privacy::shown(ann), ann.description().to_string()))
+                    .collect();
+                let annotation: String = rich_rofi(&tr!("Remove which annotation?"), annotations)?;
+                mutation::denotate(&task.uuid().to_string(), &annotation)?;
+            }
+
+            Action::Checklist => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                checklist::add_steps(&task)?;
+            }
+
+            Action::Notes => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                notes::open_notes(&task, &app.config.notes)?;
+            }
+
+            Action::Call => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                call::call(&task, &app.config.dialer)?;
+            }
+
+            Action::LinkNote => {
+                let task = task_rofi(&app.task_prompt(), &app)?;
+                vault::link_note(&task, &app.config.vault)?;
+            }
+
+            Action::RestoreBackup => back_on_escape(backup::restore())?,
+            Action::Sync => back_on_escape(sync::sync(&app.config.sync))?,
+
+            Action::Unlock => privacy::toggle_lock(),
+
+            Action::Exit => {
+                if app.config.session.summary {
+                    if let Some(summary) = session::summary() {
+                        back_on_escape(widgets::message(&summary).map_err(Into::into))?;
+                    }
+                }
+                return Ok(());
+            }
+
+            _ => loop {
+                // Starting or finishing a blocked task is usually a mistake
+                let hide_blocked = app.config.blocked.hide_when_acting
+                    && matches!(action, Action::Start | Action::Done);
+                let picked = task_rofi_with(&app.task_prompt(), &app, hide_blocked)?;
+                let mut task = picked.clone();
                 match action {
                     Action::Done => *task.status_mut() = TaskStatus::Completed,
                     Action::Start => task.set_start(Some(LocalTime::now().naive_local())),
                     Action::Stop => task.set_start::<NaiveDateTime>(None),
                     Action::Delete => *task.status_mut() = TaskStatus::Deleted,
-                    Action::Open => {
-                        task.open_annotation()?;
-                        break;
-                    }
-
-                    Action::Annotate => {
-                        let input = Rofi::<String>::new(&vec![]).prompt("annotation").run()?;
-                        let annotation =
-                            Annotation::new(LocalTime::now().naive_local().into(), input);
-                        match task.annotations_mut() {
-                            Some(annotations) => annotations.push(annotation),
-                            None => {
-                                task.set_annotations::<Vec<_>, Annotation>(Some(vec![annotation]))
-                            }
-                        }
-                    }
+                    Action::Open => return task.open_annotation(&app.config),
 
-                    Action::Wait => {
-                        let input = Rofi::<String>::new(&vec![
-                            "tomorrow".to_string(),
-                            "1h".to_string(),
-                            "2h".to_string(),
-                            "4h".to_string(),
-                            "monday".to_string(),
-                        ])
-                        .prompt("Wait until?")
-                        .run()?;
-
-                        task_command(vec![
-                            &task.uuid().to_string(),
-                            "mod",
-                            &format!("wait:{}", input),
-                        ])
-                        .context("modifying wait")?;
-                    }
+                    Action::Toggle => checklist::toggle(&mut task)?,
+                    Action::Reorder => checklist::reorder(&mut task)?,
+                    Action::Next => toggle_tag(&mut task, "next"),
 
-                    Action::Mod | Action::Add | Action::List | Action::Exit => {
+                    Action::Mod
+                    | Action::Add
+                    | Action::List
+                    | Action::Wait
+                    | Action::Due
+                    | Action::Unwait
+                    | Action::Schedule
+                    | Action::Estimate
+                    | Action::Annotate
+                    | Action::Denotate
+                    | Action::Checklist
+                    | Action::Notes
+                    | Action::LinkNote
+                    | Action::Call
+                    | Action::RescheduleOverdue
+                    | Action::SlipProject
+                    | Action::Plan
+                    | Action::Board
+                    | Action::ProjectStatus
+                    | Action::Workload
+                    | Action::Balance
+                    | Action::Stalled
+                    | Action::DependencyGraph
+                    | Action::Today
+                    | Action::Here
+                    | Action::SetLocation
+                    | Action::WhatNow
+                    | Action::Energy
+                    | Action::Someday
+                    | Action::SomedayList
+                    | Action::NextList
+                    | Action::Report
+                    | Action::Context
+                    | Action::Subtask
+                    | Action::Split
+                    | Action::Merge
+                    | Action::Copy
+                    | Action::Branch
+                    | Action::Share
+                    | Action::Focus
+                    | Action::FocusDone
+                    | Action::Unfocus
+                    | Action::Roulette
+                    | Action::Resume
+                    | Action::Plugin(_)
+                    | Action::Script(_)
+                    | Action::RestoreBackup
+                    | Action::Sync
+                    | Action::Unlock
+                    | Action::Exit => {
                         unreachable!("Already handled this case")
                     }
                 }
-                tw::save(Some(&task)).map_failure()?;
-            }
+                match conflict::save(&picked, &task, true)? {
+                    conflict::Outcome::RePick => continue,
+                    conflict::Outcome::Saved => {
+                        // Resume offers these first
+                        if matches!(action, Action::Stop) {
+                            app.state.remember("stopped", &task.uuid().to_string())?;
+                        }
+                        break;
+                    }
+                    conflict::Outcome::Aborted => break,
+                }
+            },
         }
     }
-    Ok(())
 }
 
-fn task_rofi(prompt: &str) -> Result<Task> {
-    let default_command = get_config_var("default.command")?;
-    let default_filter = get_config_var(&format!("report.{}.filter", default_command))?;
-    let mut tasks = tw::query(&default_filter).unwrap();
-    tasks.sort_unstable_by_key(|task| task.urgency().map(|u| (-u * 10_000f64) as i32));
-    let labeled_tasks: Vec<_> = tasks
-        .into_iter()
-        .map(|task| LabeledItem {
-            label: format_task(&task),
-            item: task,
-        })
-        .collect();
-    Ok(rich_rofi(prompt, labeled_tasks)?)
+/// Treats pressing escape inside a view as going back to the main menu.
+fn back_on_escape(result: Result<()>) -> Result<()> {
+    result.or_else(|e| match e.downcast_ref::<rofi::Error>() {
+        Some(rofi::Error::Interrupted) => Ok(()),
+        _ => Err(e),
+    })
 }
 
-fn get_config_var(name: &str) -> Result<String> {
-    task_command(vec!["show", name])?
-        .0
-        .lines()
-        .filter_map(|line| {
-            let parts: Vec<_> = line.split(' ').collect();
-            if parts.len() > 1 && parts[0] == name {
-                Some(parts[1..].join(" "))
-            } else {
-                None
-            }
-        })
-        .next()
-        .ok_or_else(|| anyhow!("Could not find default command"))
+fn task_rofi(prompt: &str, app: &App) -> Result<Task> {
+    task_rofi_with(prompt, app, false)
 }
 
-fn add_task(task_text: String, new_annotations: Vec<String>) -> Result<()> {
-    let mut args = vec!["add"];
-    args.extend(task_text.split_whitespace());
-    let (stdout, stderr) = task_command(args).context("adding task")?;
+/// Like `task_rofi`, with blocked tasks left out entirely when `hide_blocked` is set.
+fn task_rofi_with(prompt: &str, app: &App, hide_blocked: bool) -> Result<Task> {
+    let graph = DependencyGraph::from_tasks(export::query("status:pending")?);
+    let mut labeled_tasks = vec![];
+    let mut blocked_tasks = vec![];
+    // Rows are formatted as the export comes in and put in urgency order at the end
+    for task in default_task_stream(app)? {
+        let task = task?;
+        if !app.scripts.filter(&task)? {
+            continue;
+        }
+        let blocked = graph.is_blocked(&task.uuid().to_string());
+        if blocked && hide_blocked {
+            continue;
+        }
+        let label = match app.scripts.format(&task)? {
+            Some(label) => label,
+            None => format_task(&task, &app.config.style),
+        };
+        let label = app.config.style.emphasize(&task, label, blocked);
+        // Blocked tasks go below everything else, keeping their urgency order among themselves
+        if blocked {
+            blocked_tasks.push(Row::new(label, task));
+        } else {
+            labeled_tasks.push(Row::new(label, task));
+        }
+    }
+    let order = app.config.menu.sort;
+    labeled_tasks.sort_by(|a, b| by_order(order, &a.item, &b.item));
+    blocked_tasks.sort_by(|a, b| by_order(order, &a.item, &b.item));
+    labeled_tasks.extend(blocked_tasks);
 
-    if !stdout.starts_with("Created task ") {
-        bail!(
-            "Unexpected output from add command: `{}` / stderr: `{}`",
-            stdout,
-            stderr
+    // Rofi doesn't say when the selection moves, so the full details are a key press away
+    let can = launcher::current().capabilities();
+    let keys = if can.keys && can.message {
+        vec![(DETAILS_KEY, "Alt+i".to_string())]
+    } else {
+        vec![]
+    };
+    let mut message = labeled_tasks
+        .iter()
+        .any(|row| row.item.description().len() > app.config.style.description_width)
+        .then(|| tr!("Alt+i shows the selected task in full"))
+        .filter(|_| !keys.is_empty());
+    let mut selected = 0;
+    loop {
+        let rows = labeled_tasks
+            .iter()
+            .map(|row| Row::new(row.label.clone(), row.item.clone()))
+            .collect();
+        let (task, key) = widgets::pick_with_message(
+            prompt,
+            rows,
+            app.config.style.markup(),
+            &keys,
+            message.as_deref(),
+            selected,
+        )?;
+        if key != Some(DETAILS_KEY) {
+            return recurrence::guard(task);
+        }
+        selected = labeled_tasks
+            .iter()
+            .position(|row| row.item.uuid() == task.uuid())
+            .unwrap_or(0);
+        message = Some(task_details(&task));
+    }
+}
+
+/// The full description and the main attributes, as pango markup for rofi's message pane.
+fn task_details(task: &Task) -> String {
+    let mut lines = vec![format!(
+        "<b>{}</b>",
+        style::escape_markup(task.description())
+    )];
+    let mut attributes = vec![];
+    if let Some(project) = task.project() {
+        attributes.push(format!("project:{}", project));
+    }
+    if let Some(tags) = task.tags().filter(|tags| !tags.is_empty()) {
+        attributes.push(
+            tags.iter()
+                .map(|tag| format!("+{}", tag))
+                .collect::<Vec<_>>()
+                .join(" "),
         );
     }
-    let task_id = stdout.split_whitespace().last().unwrap();
+    if let Some(due) = task.due() {
+        let due = LocalTime.from_utc_datetime(due);
+        attributes.push(format!("due:{}", due.format("%Y-%m-%d %H:%M")));
+    }
+    if let Some(urgency) = task.urgency() {
+        attributes.push(format!("urgency:{:.1}", urgency));
+    }
+    if !attributes.is_empty() {
+        lines.push(style::escape_markup(&attributes.join("  ")).into_owned());
+    }
+    if let Some(annotations) = task.annotations().filter(|a| !a.is_empty()) {
+        lines.push(tr!("{} annotations", annotations.len()));
+    }
+    lines.join("\n")
+}
 
-    if !new_annotations.is_empty() {
-        let now: TwDate = LocalTime::now().naive_local().into();
-        let new_annotations = new_annotations
-            .iter()
-            .map(|ann| Annotation::new(now.clone(), ann.to_string()))
-            .collect::<Vec<_>>();
+/// Whether the same add text was submitted moments ago, by this run or another one started from
+/// a double-tapped hotkey, so the state is read fresh from disk.
+fn is_double_entry(input: &str, app: &App) -> Result<bool> {
+    let window = app.config.add.duplicate_window;
+    if window <= 0 {
+        return Ok(false);
+    }
+    Ok(match State::load()?.last_add {
+        Some(last) => last.text == input.trim() && LocalTime::now().timestamp() - last.at <= window,
+        None => false,
+    })
+}
+
+fn default_filter(app: &App) -> Result<String> {
+    if let Some(filter) = &app.filter {
+        return Ok(filter.clone());
+    }
+    if let Some(filter) = app.profile.as_ref().and_then(|p| p.filter.clone()) {
+        return Ok(filter);
+    }
+    let default_command = get_config_var("default.command")?;
+    get_config_var(&format!("report.{}.filter", default_command))
+}
 
-        let mut tasks = tw::query(task_id).map_failure()?;
-        if tasks.len() != 1 {
-            bail!("Querying by ID should return exactly one task");
+fn default_tasks(app: &App) -> Result<Vec<Task>> {
+    let filter = default_filter(app)?;
+    match cache::tasks(&filter) {
+        Some(mut tasks) => {
+            sort_by_urgency(&mut tasks);
+            Ok(tasks)
         }
-        let task = &mut tasks[0];
+        None => query_sorted(&filter),
+    }
+}
 
-        match task.annotations_mut() {
-            Some(annotations) => {
-                annotations.extend(new_annotations);
-            }
-            None => task.set_annotations::<Vec<_>, Annotation>(Some(new_annotations)),
-        };
-        tw::save(Some(&*task))
-            .map_err(|err| anyhow!("tw error: {}", err))
-            .context("Failed to save annotations")?;
+/// Like `default_tasks`, unsorted and without waiting for the whole export.
+fn default_task_stream(app: &App) -> Result<Box<dyn Iterator<Item = Result<Task>>>> {
+    let filter = default_filter(app)?;
+    Ok(match cache::tasks(&filter) {
+        Some(tasks) => Box::new(tasks.into_iter().map(Ok)),
+        None => Box::new(export::stream(&filter)?),
+    })
+}
+
+fn query_sorted(filter: &str) -> Result<Vec<Task>> {
+    let mut tasks = export::stream(filter)?.collect::<Result<Vec<_>>>()?;
+    sort_by_urgency(&mut tasks);
+    Ok(tasks)
+}
+
+fn sort_by_urgency(tasks: &mut [Task]) {
+    tasks.sort_by(by_urgency);
+}
+
+/// Most urgent first. Ties go to the earlier due date (tasks without one last), then the older
+/// task, then the UUID, so equally urgent tasks keep their places from one run to the next.
+fn by_urgency(a: &Task, b: &Task) -> Ordering {
+    let urgency = |task: &Task| task.urgency().map(|u| (-u * 10_000f64) as i32);
+    let due = |task: &Task| (task.due().is_none(), task.due().map(|due| **due));
+    urgency(a)
+        .cmp(&urgency(b))
+        .then_with(|| due(a).cmp(&due(b)))
+        .then_with(|| a.entry().cmp(b.entry()))
+        .then_with(|| a.uuid().cmp(b.uuid()))
+}
+
+/// `by_urgency`, after the configured order.
+fn by_order(order: SortOrder, a: &Task, b: &Task) -> Ordering {
+    let first = match order {
+        SortOrder::Urgency => Ordering::Equal,
+        SortOrder::Due => {
+            let due = |task: &Task| (task.due().is_none(), task.due().map(|due| **due));
+            due(a).cmp(&due(b))
+        }
+        SortOrder::Entry => a.entry().cmp(b.entry()),
+        SortOrder::Description => a
+            .description()
+            .to_lowercase()
+            .cmp(&b.description().to_lowercase()),
+    };
+    first.then_with(|| by_urgency(a, b))
+}
+
+/// A taskrc setting, or an error when it is unset.
+fn get_config_var(name: &str) -> Result<String> {
+    let value = match cache::setting(name) {
+        Some(value) => value,
+        None => task_command(vec!["_get", &format!("rc.{}", name)])?.0,
+    };
+    match value.trim() {
+        "" => bail!(tr!("{} is not set", name)),
+        value => Ok(value.to_string()),
     }
+}
 
-    Ok(())
+/// Splits Add's `task -- annotation -- ...` input into the task and its annotations, in the
+/// configured grammar.
+fn split_add_input(input: &str) -> Result<(String, Vec<String>)> {
+    grammar::parse(input)
 }
 
-fn mod_task(task: &mut Task) -> Result<()> {
-    let task_id = task
-        .id()
-        .map(|id| id.to_string())
-        .unwrap_or_else(|| task.uuid().to_string());
-    let input = Rofi::<String>::new(&vec![])
-        .prompt(format!("Mods for task {}", task_id))
-        .run()?;
+fn add_task(task_text: String, new_annotations: Vec<String>, use_context: bool) -> Result<()> {
+    let mut args = if use_context {
+        vec!["add"]
+    } else {
+        vec!["rc.context=none", "add"]
+    };
+    args.extend(task_text.split_whitespace());
 
-    let mut args: Vec<&str> = vec![&task_id, "mod"];
-    args.extend(input.split_whitespace());
-    task_command(args).context("modifying task")?;
+    // Later steps need the uuid the first one creates
+    let task_uuid = &RefCell::new(String::new());
+    let mut flow = Flow::default().step(tr!("add task"), || {
+        *task_uuid.borrow_mut() = create_task(args.clone()).context(tr!("adding task"))?;
+        Ok(())
+    });
+    for annotation in &new_annotations {
+        flow = flow.step(tr!("annotate \"{}\"", annotation), move || {
+            mutation::annotate(&task_uuid.borrow(), annotation)
+        });
+    }
+    flow.recovery(tr!("Show the created task"), || {
+        let (info, _) = task_command(vec![&task_uuid.borrow(), "information"])?;
+        let rows: Vec<Row<()>> = info
+            .lines()
+            .map(|line| Row::new(line.to_string(), ()))
+            .collect();
+        rich_rofi::<_, ()>(&tr!("Task {}", task_uuid.borrow()), rows)?;
+        Ok(())
+    })
+    .run()
+}
 
-    Ok(())
+/// Adds a task and its annotations without opening any menu, for requests answered off the main
+/// thread. A failed step is returned instead of offered for recovery.
+fn add_task_quietly(task_text: &str, annotations: &[String]) -> Result<String> {
+    let mut args = vec!["add"];
+    args.extend(task_text.split_whitespace());
+    let uuid = create_task(args).context(tr!("adding task"))?;
+    for annotation in annotations {
+        mutation::annotate(&uuid, annotation)?;
+    }
+    Ok(uuid)
+}
+
+/// Runs an `add` and returns the new task's UUID. IDs change whenever the list is renumbered, so
+/// whatever is done to the task next goes by UUID.
+fn create_task(mut args: Vec<&str>) -> Result<String> {
+    args.insert(0, "rc.verbose=new-uuid");
+    let (stdout, stderr) = task_command(args)?;
+    match created_uuid(&stdout) {
+        Some(uuid) => Ok(uuid.to_string()),
+        None => bail!(
+            "Unexpected output from add command: `{}` / stderr: `{}`",
+            stdout,
+            stderr
+        ),
+    }
+}
+
+/// The UUID in the output of an add run with `rc.verbose=new-uuid`. Hooks run on add and may
+/// print lines of their own around taskwarrior's.
+fn created_uuid(stdout: &str) -> Option<&str> {
+    stdout.lines().map(str::trim).find_map(|line| {
+        let rest = line.strip_prefix("Created task ")?;
+        let uuid = rest.split_whitespace().next()?.trim_end_matches('.');
+        is_uuid(uuid).then_some(uuid)
+    })
 }
 
 enum Action {
@@ -211,152 +1022,516 @@ enum Action {
     Delete,
     Done,
     List,
+    Board,
+    ProjectStatus,
+    Workload,
+    Balance,
+    Stalled,
+    DependencyGraph,
+    Today,
+    Here,
+    SetLocation,
+    WhatNow,
+    SomedayList,
+    NextList,
+    Report,
+    Context,
     Start,
     Stop,
+    Resume,
     Open,
     Mod,
+    Subtask,
+    Split,
+    Merge,
+    Copy,
+    Branch,
+    Share,
+    Focus,
+    FocusDone,
+    Unfocus,
+    Roulette,
     Wait,
+    Unwait,
+    Due,
+    Schedule,
+    Estimate,
+    Energy,
+    Someday,
+    RescheduleOverdue,
+    SlipProject,
+    Plan,
     Annotate,
+    Denotate,
+    Checklist,
+    Toggle,
+    Reorder,
+    Next,
+    Notes,
+    LinkNote,
+    Call,
+    Plugin(Plugin),
+    Script(String),
+    RestoreBackup,
+    Sync,
+    Unlock,
     Exit,
 }
 
 impl Action {
-    fn all() -> Vec<Self> {
+    fn all(app: &App, focused: bool) -> Vec<Self> {
+        let mut actions = vec![];
+        if focused {
+            actions.extend(vec![Self::FocusDone, Self::Unfocus]);
+        }
+        actions.extend(Self::builtin());
+        actions.extend(app.plugins.iter().cloned().map(Self::Plugin));
+        actions.extend(app.scripts.actions().into_iter().map(Self::Script));
+        actions.extend(vec![
+            Self::RestoreBackup,
+            Self::Sync,
+            Self::Unlock,
+            Self::Exit,
+        ]);
+        if app.config.read_only {
+            actions.retain(|action| !action.mutating());
+        }
+        let offered = &app.config.menu.actions;
+        if !offered.is_empty() {
+            actions.retain(|action| {
+                offered
+                    .iter()
+                    .any(|name| action.name().eq_ignore_ascii_case(name))
+            });
+        }
+
+        let preferred = app.profile.as_ref().and_then(|p| p.action.as_ref());
+        if let Some(preferred) = preferred {
+            let position = actions
+                .iter()
+                .position(|action| action.name().eq_ignore_ascii_case(preferred));
+            if let Some(position) = position {
+                let action = actions.remove(position);
+                actions.insert(0, action);
+            }
+        }
+        actions
+    }
+
+    /// The built-in actions in menu order, leaving out the focus ones and those listed after
+    /// plugins and scripts.
+    fn builtin() -> Vec<Self> {
         vec![
             Self::List,
+            Self::Board,
+            Self::ProjectStatus,
+            Self::Workload,
+            Self::Balance,
+            Self::Stalled,
+            Self::DependencyGraph,
+            Self::Today,
+            Self::Here,
+            Self::SetLocation,
+            Self::WhatNow,
+            Self::SomedayList,
+            Self::NextList,
+            Self::Report,
+            Self::Context,
             Self::Add,
             Self::Done,
             Self::Start,
             Self::Stop,
+            Self::Resume,
             Self::Delete,
             Self::Open,
             Self::Mod,
+            Self::Subtask,
+            Self::Split,
+            Self::Merge,
+            Self::Copy,
+            Self::Branch,
+            Self::Share,
+            Self::Focus,
+            Self::Roulette,
             Self::Wait,
+            Self::Unwait,
+            Self::Due,
+            Self::Schedule,
+            Self::Estimate,
+            Self::Energy,
+            Self::Someday,
+            Self::RescheduleOverdue,
+            Self::SlipProject,
+            Self::Plan,
             Self::Annotate,
-            Self::Exit,
+            Self::Denotate,
+            Self::Checklist,
+            Self::Toggle,
+            Self::Reorder,
+            Self::Next,
+            Self::Notes,
+            Self::LinkNote,
+            Self::Call,
         ]
     }
-}
 
-impl std::fmt::Display for Action {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Action::Add => "Add",
-                Action::Delete => "Delete",
-                Action::Done => "Done",
-                Action::List => "List",
-                Action::Start => "Start",
-                Action::Stop => "Stop",
-                Action::Open => "Open",
-                Action::Mod => "Mod",
-                Action::Wait => "Wait",
-                Action::Annotate => "Annotate",
-                Action::Exit => "Exit (Escape)",
-            }
-        )
-    }
-}
-
-fn format_task(task: &Task) -> String {
-    let mut parts = vec![];
-    let max_desc = 60;
-
-    if let Some(id) = task.id() {
-        parts.push(format!("[{:>2}]", id));
-    } else {
-        parts.push("[--]".to_string());
+    /// The command-line word and label of each built-in action that can be named on the command
+    /// line, for the CLI definition. Plugin and script actions are only known at startup.
+    fn commands() -> Vec<(String, String)> {
+        let mut actions = vec![Self::FocusDone, Self::Unfocus];
+        actions.extend(Self::builtin());
+        actions.extend(vec![Self::RestoreBackup, Self::Sync, Self::Unlock]);
+        actions
+            .iter()
+            .map(|action| (action.command_name(), action.name().to_string()))
+            .collect()
     }
+}
 
-    if task.description().len() <= max_desc {
-        parts.push(format!("{:<width$}", task.description(), width = max_desc));
-    } else {
-        let truncated = &task.description()[..max_desc - 3];
-        parts.push(format!("{}...", truncated));
+impl Action {
+    /// The action offered under `name`, its label or `command_name`, ignoring case.
+    fn named(app: &App, name: &str) -> Result<Self> {
+        let focused = focus::focused_task(&app.state)?.is_some();
+        Self::all(app, focused)
+            .into_iter()
+            .find(|action| {
+                action.command_name() == name.to_lowercase()
+                    || action.name().eq_ignore_ascii_case(name)
+            })
+            .ok_or_else(|| anyhow!(tr!("No action named `{}` is offered", name)))
     }
 
-    if let Some(urgency) = task.urgency() {
-        parts.push(format!("(u={:+.2})", urgency));
+    /// The label as a command-line word, like `project-status`.
+    fn command_name(&self) -> String {
+        self.name()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join("-")
     }
 
-    if let Some(project) = task.project() {
-        parts.push(format!("proj:{}", project));
+    /// The untranslated label, which profiles use to name their preferred action.
+    fn name(&self) -> &str {
+        match self {
+            Action::Add => "Add",
+            Action::Delete => "Delete",
+            Action::Done => "Done",
+            Action::List => "List",
+            Action::Board => "Board",
+            Action::ProjectStatus => "Project status",
+            Action::Workload => "Workload",
+            Action::Balance => "Balance",
+            Action::Stalled => "Stalled",
+            Action::DependencyGraph => "Dependency graph",
+            Action::Today => "Today",
+            Action::Here => "Here",
+            Action::SetLocation => "Set location",
+            Action::WhatNow => "What can I do now?",
+            Action::SomedayList => "Someday list",
+            Action::NextList => "Next list",
+            Action::Report => "Report",
+            Action::Context => "Context",
+            Action::Start => "Start",
+            Action::Stop => "Stop",
+            Action::Resume => "Resume",
+            Action::Open => "Open",
+            Action::Mod => "Mod",
+            Action::Subtask => "Subtask",
+            Action::Split => "Split",
+            Action::Merge => "Merge",
+            Action::Copy => "Copy",
+            Action::Branch => "Branch",
+            Action::Share => "Share",
+            Action::Focus => "Focus",
+            Action::FocusDone => "Done with focused task",
+            Action::Unfocus => "Stop focusing",
+            Action::Roulette => "Roulette",
+            Action::Wait => "Wait",
+            Action::Due => "Due",
+            Action::Unwait => "Unwait",
+            Action::Schedule => "Schedule",
+            Action::Estimate => "Estimate",
+            Action::Energy => "Energy",
+            Action::Someday => "Move to someday",
+            Action::RescheduleOverdue => "Reschedule overdue",
+            Action::SlipProject => "Slip project",
+            Action::Plan => "Plan",
+            Action::Annotate => "Annotate",
+            Action::Denotate => "Remove annotation",
+            Action::Checklist => "Checklist",
+            Action::Toggle => "Toggle checklist step",
+            Action::Reorder => "Reorder checklist",
+            Action::Next => "Next",
+            Action::Notes => "Notes",
+            Action::LinkNote => "Link note",
+            Action::Call => "Call",
+            Action::Plugin(plugin) => plugin.name.as_str(),
+            Action::Script(name) => name.as_str(),
+            Action::RestoreBackup => "Restore backup",
+            Action::Sync => "Sync",
+            Action::Unlock if privacy::unlocked() => "Lock private annotations",
+            Action::Unlock => "Unlock private annotations",
+            Action::Exit => "Exit (Escape)",
+        }
     }
 
-    parts.join(" ")
-}
+    /// Whether the action can change tasks, which read-only mode hides. Views stay, with their
+    /// follow-up menus cut down to what changes nothing; plugins and scripts could do anything.
+    fn mutating(&self) -> bool {
+        match self {
+            Action::List
+            | Action::Board
+            | Action::ProjectStatus
+            | Action::Workload
+            | Action::Balance
+            | Action::Stalled
+            | Action::Today
+            | Action::Here
+            | Action::WhatNow
+            | Action::SomedayList
+            | Action::NextList
+            | Action::Report
+            | Action::Context
+            | Action::DependencyGraph
+            | Action::SetLocation
+            | Action::Open
+            | Action::Copy
+            | Action::Branch
+            | Action::Share
+            | Action::Call
+            | Action::Unlock
+            | Action::Exit => false,
 
-struct LabeledItem<T> {
-    label: String,
-    item: T,
+            Action::Add
+            | Action::Delete
+            | Action::Done
+            | Action::Start
+            | Action::Stop
+            | Action::Resume
+            | Action::Mod
+            | Action::Subtask
+            | Action::Split
+            | Action::Merge
+            | Action::Focus
+            | Action::FocusDone
+            | Action::Unfocus
+            | Action::Roulette
+            | Action::Wait
+            | Action::Due
+            | Action::Unwait
+            | Action::Schedule
+            | Action::Estimate
+            | Action::Energy
+            | Action::Someday
+            | Action::RescheduleOverdue
+            | Action::SlipProject
+            | Action::Plan
+            | Action::Annotate
+            | Action::Denotate
+            | Action::Checklist
+            | Action::Toggle
+            | Action::Reorder
+            | Action::Next
+            | Action::Notes
+            | Action::LinkNote
+            | Action::Plugin(_)
+            | Action::Script(_)
+            | Action::RestoreBackup
+            | Action::Sync => true,
+        }
+    }
 }
 
-impl<T> From<T> for LabeledItem<T>
-where
-    T: Display,
-{
-    fn from(item: T) -> Self {
-        Self {
-            label: item.to_string(),
-            item,
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Plugin(_) | Action::Script(_) => write!(f, "{}", self.name()),
+            _ => write!(f, "{}", tr!(self.name())),
         }
     }
 }
 
-impl<T> Display for LabeledItem<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.label)
+/// Adds the tag when the task doesn't have it, otherwise removes it.
+fn toggle_tag(task: &mut Task, tag: &str) {
+    let mut tags = task.tags().cloned().unwrap_or_default();
+    match tags.iter().position(|t| t == tag) {
+        Some(idx) => {
+            tags.remove(idx);
+        }
+        None => tags.push(tag.to_string()),
     }
+    task.set_tags::<_, String>(Some(tags).filter(|tags| !tags.is_empty()));
 }
 
-fn rich_rofi<T, U>(prompt: &str, items: Vec<T>) -> Result<U, rofi::Error>
-where
-    T: Into<LabeledItem<U>>,
-{
-    let mut items: Vec<LabeledItem<U>> = items.into_iter().map(|i| i.into()).collect();
-    let labels = items.iter().map(|i| &i.label).collect();
-    let idx = Rofi::new(&labels).prompt(prompt).run_index()?;
-    // use `swap_remove` so we don't have to re-order the list we're about the throw away anyways
-    Ok(items.swap_remove(idx).item)
+/// The custom key (`-kb-custom-N`) that shows a task's details in the task list.
+const DETAILS_KEY: u8 = 19;
+
+/// A task's row label. With `style.columns` the ID, description, urgency and project are
+/// tab-separated columns (empty ones included, to keep the rest aligned) and everything after
+/// the project shares the last one; otherwise the columns are padded with spaces.
+///
+/// Writing to a `String` can't fail, hence the ignored `write!` results.
+fn format_task(task: &Task, style: &Style) -> String {
+    let columns = style.columns;
+    let separator = if columns { '\t' } else { ' ' };
+    let width = style.description_width;
+    let mut label = String::with_capacity(width + 32);
+
+    if let Some(heat) = style.heat(task) {
+        label.push_str(heat);
+        label.push(' ');
+    }
+    let _ = match task.id() {
+        Some(id) => write!(label, "[{:>2}]", id),
+        None => write!(label, "[--]"),
+    };
+
+    label.push(separator);
+    let description = task.description();
+    if description.len() <= width {
+        if columns {
+            label.push_str(description);
+        } else {
+            let _ = write!(label, "{:<width$}", description, width = width);
+        }
+    } else {
+        // Cut on a character boundary, which a byte count alone might not be
+        let end = (0..=width.saturating_sub(3))
+            .rev()
+            .find(|idx| description.is_char_boundary(*idx))
+            .unwrap_or(0);
+        label.push_str(&description[..end]);
+        label.push_str("...");
+    }
+
+    // Empty columns are kept so the ones after them stay aligned
+    match task.urgency() {
+        Some(urgency) => {
+            label.push(separator);
+            let _ = write!(label, "(u={:+.2})", urgency);
+        }
+        None if columns => label.push(separator),
+        None => {}
+    }
+    match task.project() {
+        Some(project) => {
+            label.push(separator);
+            label.push_str("proj:");
+            label.push_str(project);
+        }
+        None if columns => label.push(separator),
+        None => {}
+    }
+
+    // Everything after the project shares the last column
+    if columns {
+        label.push('\t');
+    }
+    let mut first_part = true;
+    let mut part = |label: &mut String| {
+        if !(columns && first_part) {
+            label.push(' ');
+        }
+        first_part = false;
+    };
+
+    if let Some(depends) = task.depends().filter(|depends| !depends.is_empty()) {
+        part(&mut label);
+        let _ = write!(label, "[{} sub]", depends.len());
+    }
+
+    // Shown so a task left running by mistake stands out
+    if let Some(start) = task.start() {
+        let started = LocalTime.from_utc_datetime(start);
+        let elapsed = Duration::minutes((LocalTime::now() - started).num_minutes().max(0));
+        part(&mut label);
+        let _ = write!(
+            label,
+            "{} {}",
+            style.glyph(Glyph::Play),
+            duration::format(elapsed)
+        );
+    }
+
+    // Waiting tasks only show up with filters that ask for them, so say when they come back
+    if let Some(wait) = task.wait() {
+        let wait = LocalTime.from_utc_datetime(wait);
+        if wait > LocalTime::now() {
+            part(&mut label);
+            let _ = write!(label, "wait:{}", wait.format("%Y-%m-%d"));
+        }
+    }
+
+    label
 }
 
 trait TaskExt {
-    fn open_annotation(&self) -> Result<()>;
+    fn open_annotation(&self, config: &Config) -> Result<()>;
+    fn link_annotations(&self) -> Vec<&Annotation>;
+    fn uda_string(&self, name: &str) -> Option<String>;
 }
 
 impl TaskExt for Task {
-    fn open_annotation(&self) -> Result<()> {
-        let annotations = self
-            .annotations()
-            .ok_or_else(|| anyhow!("No annotations found"))?;
-        let with_links: Vec<_> = annotations
-            .iter()
+    fn uda_string(&self, name: &str) -> Option<String> {
+        self.uda().get(name).map(|value| match value {
+            UDAValue::Str(s) => s.clone(),
+            UDAValue::U64(n) => n.to_string(),
+            UDAValue::F64(n) => n.to_string(),
+        })
+    }
+
+    fn link_annotations(&self) -> Vec<&Annotation> {
+        self.annotations()
+            .into_iter()
+            .flatten()
             .filter(|ann| {
                 ann.description().starts_with("https://")
                     || ann.description().starts_with("http://")
+                    || ann.description().starts_with("file://")
             })
-            .collect();
+            .collect()
+    }
 
-        let choice: &Annotation = match with_links.len() {
-            0 => bail!("No annotation links found"),
-            1 => with_links[0],
-            _ => {
-                let mut labeled: Vec<_> = with_links
-                    .into_iter()
-                    .map(|ann| LabeledItem {
-                        label: format!("{} {}", ann.entry().format("%Y-%m-%d"), ann.description()),
-                        item: ann,
-                    })
-                    .collect();
-                labeled.sort_by(|a, b| a.label.cmp(&b.label).reverse());
+    fn open_annotation(&self, config: &Config) -> Result<()> {
+        let vault = &config.vault;
+        let note = vault::note_url(self, vault);
+        if self.annotations().is_none() && note.is_none() {
+            bail!(tr!("No annotations found"));
+        }
 
-                rich_rofi("Choose annotation", labeled).context("Couldn't choose an annotation")?
-            }
+        let mut labeled: Vec<_> = self
+            .link_annotations()
+            .into_iter()
+            .map(|ann| {
+                Row::new(
+                    format!("{} {}", ann.entry().format("%Y-%m-%d"), privacy::shown(ann)),
+                    ann.description().to_string(),
+                )
+            })
+            .collect();
+        labeled.sort_by(|a, b| a.label.cmp(&b.label).reverse());
+        // The linked note comes first, above the annotations
+        if let Some(url) = note {
+            let label = tr!("Note in {}", vault.name.clone().unwrap_or_default());
+            labeled.insert(0, Row::new(label, url));
+        }
+
+        let choice: String = match labeled.len() {
+            0 => bail!(tr!("No annotation links found")),
+            1 => labeled.swap_remove(0).item,
+            _ => rich_rofi(&tr!("Choose annotation"), labeled)
+                .context(tr!("Couldn't choose an annotation"))?,
         };
 
-        open::that(choice.description()).context("Could not open item specified by annotation")?;
+        // Encrypted notes can't go to the desktop's handler
+        if let Some(path) = choice
+            .strip_prefix("file://")
+            .filter(|p| p.ends_with(".age"))
+        {
+            return notes::open_file(Path::new(path), &config.notes);
+        }
+        open::that(&choice).context(tr!("Could not open item specified by annotation"))?;
 
         Ok(())
     }
@@ -372,15 +1547,41 @@ impl<T> MapFailure for Result<T, failure::Error> {
     type MappedError = Result<T, anyhow::Error>;
 
     fn map_failure(self) -> Self::MappedError {
-        self.map_err(|err| anyhow!("tw error: {}", err))
+        self.map_err(|err| anyhow!(tr!("tw error: {}", err)))
     }
 }
 
+/// Runs a configured command line, split on whitespace with `placeholder` replaced by `value` in
+/// each argument.
+fn run_template(template: &str, placeholder: &str, value: &str) -> Result<()> {
+    let args: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| arg.replace(placeholder, value))
+        .collect();
+    if args.is_empty() {
+        bail!(tr!("The configured command is empty"));
+    }
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .with_context(|| tr!("running {}", args[0]))?;
+    if !status.success() {
+        bail!(tr!("`{}` failed with {}", args.join(" "), status));
+    }
+    Ok(())
+}
+
+/// Runs `task` with `args`. Helper commands like `_get` and `_show` only read, so they run with
+/// hooks off and nothing but their own output, which is parsed.
 fn task_command(args: Vec<&str>) -> Result<(String, String)> {
-    let result = Command::new("task")
+    let mut command = Command::new("task");
+    if command_word(&args).is_some_and(|word| word.starts_with('_')) {
+        command.args(["rc.hooks=off", "rc.verbose=nothing"]);
+    }
+    let result = command
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .args(args)
+        .args(&args)
         .spawn()?
         .wait_with_output()?;
 
@@ -388,8 +1589,122 @@ fn task_command(args: Vec<&str>) -> Result<(String, String)> {
     let stderr = String::from_utf8(result.stderr)?;
 
     if !result.status.success() {
-        bail!("stdout: {} / stderr: {}", stdout, stderr);
+        bail!(tr!("stdout: {} / stderr: {}", stdout, stderr));
     }
 
+    webhook::command(&args);
+    session::command(&args);
     Ok((stdout, stderr))
 }
+
+/// The subcommand in `task` arguments, like `mod` in `rc.bulk=0 <uuid> <uuid> mod due:`: the
+/// first argument that is neither an `rc.` override nor part of the filter before it. Anything
+/// after it, such as the text of an add, isn't looked at.
+fn command_word<'a>(args: &[&'a str]) -> Option<&'a str> {
+    args.iter()
+        .copied()
+        .find(|arg| !arg.starts_with("rc.") && !is_filter_word(arg))
+}
+
+/// Whether `arg` is one of the filter words this tool puts before a command: UUIDs, IDs and ranges
+/// of them, attributes like `status:pending`, tags and parentheses.
+fn is_filter_word(arg: &str) -> bool {
+    is_uuid(arg)
+        || arg
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '-')
+        || arg.contains(':')
+        || arg.starts_with(['+', '-', '('])
+        || arg.ends_with(')')
+        || ["and", "or", "xor"].contains(&arg)
+}
+
+/// Whether `arg` is a task's UUID, rather than a filter that could match any number of tasks.
+fn is_uuid(arg: &str) -> bool {
+    arg.len() == 36
+        && arg.char_indices().all(|(idx, c)| match idx {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: &str = "6fd0ba4e-cd2a-4a84-9b2b-0e1b3a6f0c11";
+
+    #[test]
+    fn created_uuid_alone() {
+        let stdout = format!("Created task {}.\n", UUID);
+        assert_eq!(created_uuid(&stdout), Some(UUID));
+    }
+
+    #[test]
+    fn created_uuid_among_hook_lines() {
+        let stdout = format!(
+            "on-add: checking project\n  Created task {}.\nsynced 1 task to the server\n",
+            UUID
+        );
+        assert_eq!(created_uuid(&stdout), Some(UUID));
+    }
+
+    #[test]
+    fn created_uuid_skips_hook_lines_that_look_alike() {
+        let stdout = format!(
+            "Created task list for the standup.\nCreated task {}.\n",
+            UUID
+        );
+        assert_eq!(created_uuid(&stdout), Some(UUID));
+    }
+
+    #[test]
+    fn created_uuid_missing() {
+        assert_eq!(created_uuid("Couldn't create the task.\n"), None);
+        assert_eq!(created_uuid(""), None);
+    }
+
+    #[test]
+    fn command_word_of_helpers() {
+        assert_eq!(command_word(&["_get", "rc.context"]), Some("_get"));
+        assert_eq!(command_word(&["rc.json.array=off", "_show"]), Some("_show"));
+    }
+
+    #[test]
+    fn command_word_after_a_bulk_filter() {
+        let args = [
+            "rc.confirmation=off",
+            "rc.bulk=0",
+            UUID,
+            UUID,
+            "mod",
+            "due:tomorrow",
+        ];
+        assert_eq!(command_word(&args), Some("mod"));
+        assert_eq!(command_word(&["12,14-16", "+work", "done"]), Some("done"));
+    }
+
+    #[test]
+    fn command_word_after_overrides() {
+        let args = [
+            "rc.verbose=new-uuid",
+            "rc.context=none",
+            "add",
+            "call",
+            "mom",
+        ];
+        assert_eq!(command_word(&args), Some("add"));
+    }
+
+    #[test]
+    fn command_word_ignores_the_text_after_it() {
+        assert_eq!(
+            command_word(&["add", "fix", "_private", "thing"]),
+            Some("add")
+        );
+        assert_eq!(
+            command_word(&[UUID, "annotate", "--", "__init__", "bug"]),
+            Some("annotate")
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! `taskwarrior-rofi menu`: the picker as a dmenu-style filter for other scripts. Rows are read
+//! from stdin and the chosen ones written to stdout.
+//!
+//! - `-p <prompt>`
+//! - `-markup` treats rows as pango markup
+//! - `-multi` lets any number of rows be ticked, written one per line
+//! - `-kb-custom-N <key>` (N from 1 to 19) adds a key that also chooses a row, exiting with
+//!   `9 + N` like rofi does
+//!
+//! Nothing chosen exits with 1.
+
+use crate::widgets::{self, Row};
+use anyhow::{anyhow, bail, Result};
+use std::io::{self, BufRead};
+
+pub fn menu(args: &[String]) -> Result<i32> {
+    let mut prompt = String::new();
+    let mut markup = false;
+    let mut multi = false;
+    let mut keys = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| anyhow!(tr!("{} needs a value", arg)))
+        };
+        match arg.as_str() {
+            "-p" => prompt = value()?,
+            "-markup" => markup = true,
+            "-multi" => multi = true,
+            custom if custom.starts_with("-kb-custom-") => {
+                let number = custom["-kb-custom-".len()..]
+                    .parse()
+                    .ok()
+                    .filter(|number| (1..=19).contains(number))
+                    .ok_or_else(|| anyhow!(tr!("unknown custom key {}", custom)))?;
+                keys.push((number, value()?));
+            }
+            other => bail!(tr!("unknown option {}", other)),
+        }
+    }
+
+    let rows: Vec<String> = io::stdin().lock().lines().collect::<Result<_, _>>()?;
+    if multi {
+        let items = rows.into_iter().map(Row::from).collect();
+        let chosen = match widgets::multi_select(&prompt, items) {
+            Err(rofi::Error::Interrupted) => return Ok(1),
+            chosen => chosen?,
+        };
+        for row in &chosen {
+            println!("{}", row);
+        }
+        return Ok(if chosen.is_empty() { 1 } else { 0 });
+    }
+
+    match widgets::pick_keyed(&prompt, rows, markup, &keys) {
+        Ok((row, key)) => {
+            println!("{}", row);
+            Ok(key.map_or(0, |key| 9 + key as i32))
+        }
+        Err(rofi::Error::Interrupted) | Err(rofi::Error::Blank) => Ok(1),
+        Err(err) => Err(err.into()),
+    }
+}
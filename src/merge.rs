@@ -0,0 +1,115 @@
+//! Merging duplicate tasks, e.g. after a bugwarrior import created the same task twice.
+//!
+//! The kept task gains the other's tags and annotations and the earlier of the two due dates,
+//! then the other task is deleted. Both are backed up first, and either one changing since it was
+//! picked goes through the usual conflict check.
+
+use crate::{
+    backup,
+    conflict::{self, Outcome},
+    format_task, mutation, privacy,
+    style::{Glyph, Style},
+    widgets,
+};
+use anyhow::{bail, Result};
+use task_hookrs::{annotation::Annotation, status::TaskStatus, task::Task};
+
+pub fn merge(keep: Task, other: Task, style: &Style) -> Result<()> {
+    if keep.uuid() == other.uuid() {
+        bail!(tr!("Can't merge a task with itself"));
+    }
+
+    let merged = merged(&keep, &other);
+    let annotations = new_annotations(&keep, &other);
+    let preview = preview(&keep, &merged, &annotations, &other, style);
+    if !widgets::confirm(&tr!("Merge tasks?"), &tr!("Merge"), &tr!("Cancel"), preview)? {
+        return Ok(());
+    }
+
+    let uuid = keep.uuid().to_string();
+    backup::snapshot(&[&uuid, &other.uuid().to_string()], "merge")?;
+    if let Outcome::Aborted = conflict::save(&keep, &merged, false)? {
+        return Ok(());
+    }
+    for annotation in &annotations {
+        mutation::annotate(&uuid, annotation.description())?;
+    }
+    let mut deleted = other.clone();
+    *deleted.status_mut() = TaskStatus::Deleted;
+    conflict::save(&other, &deleted, false)?;
+    Ok(())
+}
+
+/// The kept task with the other's tags and the earlier due date. Annotations are added
+/// separately, with `annotate`.
+fn merged(keep: &Task, other: &Task) -> Task {
+    let mut merged = keep.clone();
+
+    let mut tags = keep.tags().cloned().unwrap_or_default();
+    for tag in other.tags().into_iter().flatten() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    if !tags.is_empty() {
+        merged.set_tags::<Vec<_>, String>(Some(tags));
+    }
+
+    let due = match (keep.due(), other.due()) {
+        (Some(a), Some(b)) => Some(if **b < **a { b } else { a }),
+        (a, b) => a.or(b),
+    };
+    merged.set_due(due.cloned());
+
+    merged
+}
+
+/// The other task's annotations that the kept one doesn't have yet.
+fn new_annotations(keep: &Task, other: &Task) -> Vec<Annotation> {
+    let existing = keep.annotations().cloned().unwrap_or_default();
+    let mut added: Vec<Annotation> = vec![];
+    for annotation in other.annotations().into_iter().flatten() {
+        let known = |a: &Annotation| a.description() == annotation.description();
+        if !existing.iter().any(known) && !added.iter().any(known) {
+            added.push(annotation.clone());
+        }
+    }
+    added
+}
+
+fn preview(
+    keep: &Task,
+    merged: &Task,
+    annotations: &[Annotation],
+    other: &Task,
+    style: &Style,
+) -> Vec<String> {
+    let mut lines = vec![format!("keep: {}", format_task(keep, style))];
+
+    let old_tags = keep.tags().cloned().unwrap_or_default();
+    for tag in merged.tags().into_iter().flatten() {
+        if !old_tags.contains(tag) {
+            lines.push(format!("+ tag {}", tag));
+        }
+    }
+
+    for annotation in annotations {
+        lines.push(format!("+ annotation {}", privacy::shown(annotation)));
+    }
+
+    if keep.due() != merged.due() {
+        let format = |task: &Task| {
+            task.due()
+                .map_or("none".to_string(), |due| due.format("%Y-%m-%d").to_string())
+        };
+        lines.push(format!(
+            "due {} {} {}",
+            format(keep),
+            style.glyph(Glyph::ArrowRight),
+            format(merged)
+        ));
+    }
+
+    lines.push(format!("delete: {}", format_task(other, style)));
+    lines
+}
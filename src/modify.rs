@@ -0,0 +1,174 @@
+//! The Mod action as a guided sequence: pick an attribute, then its value, until "apply". Typing
+//! mods directly is still there as "custom".
+//!
+//! The apply row shows how the mods would change the task's urgency. Mods other than project,
+//! due, priority and tags (including custom ones) don't count towards it.
+
+use crate::{
+    dates, recurrence, task_command,
+    urgency::{self, Coefficients},
+    widgets::{self, rich_rofi, Row},
+    App,
+};
+use anyhow::{Context, Result};
+use chrono::{offset::Local as LocalTime, NaiveDateTime, TimeZone};
+use task_hookrs::{priority::TaskPriority, task::Task};
+
+enum Attribute {
+    Apply,
+    Project,
+    Due,
+    Priority,
+    AddTag,
+    RemoveTag,
+    Custom,
+}
+
+pub fn mod_task(task: &Task, app: &mut App) -> Result<()> {
+    // The ID is only shown; the change goes by UUID in case the list was renumbered meanwhile
+    let uuid = task.uuid().to_string();
+    let task_id = task
+        .id()
+        .filter(|id| *id != 0)
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| uuid.clone());
+    let mut mods: Vec<String> = vec![];
+    let mut coefficients = None;
+
+    loop {
+        let mut rows = vec![];
+        if !mods.is_empty() {
+            if coefficients.is_none() {
+                coefficients = Some(Coefficients::load()?);
+            }
+            let mut changed = task.clone();
+            for m in &mods {
+                apply(&mut changed, m)?;
+            }
+            let before = task.urgency().unwrap_or(0.0);
+            let after = urgency::preview(task, &changed, coefficients.as_ref().expect("loaded"));
+            rows.push(Row::new(
+                tr!(
+                    "Apply: {} (urgency {} → {})",
+                    mods.join(" "),
+                    format!("{:.1}", before),
+                    format!("{:.1}", after)
+                ),
+                Attribute::Apply,
+            ));
+        }
+        rows.extend(
+            vec![
+                ("project", Attribute::Project),
+                ("due", Attribute::Due),
+                ("priority", Attribute::Priority),
+                ("+tag", Attribute::AddTag),
+                ("-tag", Attribute::RemoveTag),
+                ("custom", Attribute::Custom),
+            ]
+            .into_iter()
+            .map(|(label, item)| Row::new(tr!(label), item)),
+        );
+
+        match rich_rofi(&tr!("Mods for task {}", task_id), rows)? {
+            Attribute::Apply => {
+                let due = task
+                    .due()
+                    .map(|due| LocalTime.from_utc_datetime(due).naive_local());
+                if recurrence::confirm(mods.iter().map(String::as_str), due)? {
+                    break;
+                }
+            }
+            Attribute::Project => {
+                let input = widgets::text_with(&tr!("project"), &completions("_projects")?)?;
+                mods.push(format!("project:{}", input.trim()));
+            }
+            Attribute::Due => {
+                let date = dates::pick_date(&tr!("Due when?"), &dates::DUE_PRESETS, &app.config)?;
+                mods.push(format!("due:{}", dates::pick_time(&date)?));
+            }
+            Attribute::Priority => {
+                let priorities = vec!["H", "M", "L", "none"]
+                    .into_iter()
+                    .map(|p| Row::new(p.to_string(), if p == "none" { "" } else { p }))
+                    .collect();
+                let priority: &str = rich_rofi(&tr!("priority"), priorities)?;
+                mods.push(format!("priority:{}", priority));
+            }
+            Attribute::AddTag => {
+                let input = widgets::text_with(&tr!("tag to add"), &completions("_tags")?)?;
+                mods.push(format!("+{}", input.trim().trim_start_matches('+')));
+            }
+            Attribute::RemoveTag => {
+                let tags = task.tags().cloned().unwrap_or_default();
+                let input = widgets::text_with(&tr!("tag to remove"), &tags)?;
+                mods.push(format!("-{}", input.trim().trim_start_matches('-')));
+            }
+            Attribute::Custom => {
+                let input = widgets::text_with(
+                    &tr!("Mods for task {}", task_id),
+                    &app.state.history("mod"),
+                )?;
+                mods.extend(input.split_whitespace().map(str::to_string));
+            }
+        }
+    }
+
+    app.state.remember("mod", &mods.join(" "))?;
+    let mut args: Vec<&str> = vec![&uuid, "mod"];
+    args.extend(mods.iter().map(String::as_str));
+    task_command(args).context(tr!("modifying task"))?;
+
+    Ok(())
+}
+
+/// Makes a mod to a local copy of the task, for the urgency preview. Mods it doesn't know are left
+/// out.
+fn apply(task: &mut Task, m: &str) -> Result<()> {
+    if let Some(project) = m.strip_prefix("project:") {
+        task.set_project(Some(project.to_string()).filter(|p| !p.is_empty()));
+    } else if let Some(priority) = m.strip_prefix("priority:") {
+        task.set_priority(match priority {
+            "H" => Some(TaskPriority::High),
+            "M" => Some(TaskPriority::Medium),
+            "L" => Some(TaskPriority::Low),
+            _ => None,
+        });
+    } else if let Some(due) = m.strip_prefix("due:") {
+        if due.is_empty() {
+            task.set_due::<NaiveDateTime>(None);
+        } else if let Some(due) = resolve_date(due)? {
+            task.set_due(Some(due));
+        }
+    } else if let Some(tag) = m.strip_prefix('+') {
+        let mut tags = task.tags().cloned().unwrap_or_default();
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+        task.set_tags::<_, String>(Some(tags));
+    } else if let Some(tag) = m.strip_prefix('-') {
+        let mut tags = task.tags().cloned().unwrap_or_default();
+        tags.retain(|t| t != tag);
+        task.set_tags::<_, String>(Some(tags).filter(|tags| !tags.is_empty()));
+    }
+    Ok(())
+}
+
+/// A `due:` value as taskwarrior would store it, in UTC.
+fn resolve_date(value: &str) -> Result<Option<NaiveDateTime>> {
+    // Durations come back as durations; the preview leaves the due date as it was
+    let local = match dates::calc(value)? {
+        Some(local) => local,
+        None => return Ok(None),
+    };
+    Ok(LocalTime
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.naive_utc()))
+}
+
+/// Values taskwarrior already knows about, from a helper command like `_projects`.
+fn completions(command: &str) -> Result<Vec<String>> {
+    let (stdout, _) = task_command(vec![command])?;
+    Ok(stdout.lines().map(str::to_string).collect())
+}
@@ -0,0 +1,16 @@
+//! Changes made through `task` commands instead of saving whole task objects with `tw::save`, so
+//! concurrent edits to other fields survive and hooks see the change for what it is.
+
+use crate::task_command;
+use anyhow::{Context, Result};
+
+/// Everything after `--` is taken literally, so text like `due:tomorrow` isn't read as a mod.
+pub fn annotate(uuid: &str, text: &str) -> Result<()> {
+    task_command(vec![uuid, "annotate", "--", text]).context(tr!("annotating task"))?;
+    Ok(())
+}
+
+pub fn denotate(uuid: &str, text: &str) -> Result<()> {
+    task_command(vec![uuid, "denotate", "--", text]).context(tr!("removing annotation"))?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Native messaging host for a browser companion extension: length-prefixed JSON over stdio, as
+//! Chrome and Firefox speak it. Browsers start the host with the extension's origin (Chrome) or
+//! the manifest path (Firefox) as the first argument, which is how `main` recognizes this mode.
+//!
+//! Requests and their answers:
+//!
+//! - `{"type": "add", "title": "...", "url": "..."}` adds a task named after the page, annotated
+//!   with its URL, answering `{"uuid": "..."}`
+//! - `{"type": "counts"}` answers `{"pending": n, "due_today": n, "overdue": n}`
+//!
+//! Failures answer `{"error": "..."}` and the host keeps going until the browser closes stdin.
+
+use crate::{create_task, mutation, query_sorted};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Chrome's limit for messages to the host.
+const MAX_MESSAGE: usize = 4 * 1024 * 1024;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Add { title: String, url: Option<String> },
+    Counts,
+}
+
+/// Whether the process was started by a browser as a native messaging host.
+pub fn started_by_browser(arg: &str) -> bool {
+    arg.starts_with("chrome-extension://") || arg.ends_with(".json")
+}
+
+pub fn host() -> Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    while let Some(message) = read_message(&mut stdin)? {
+        let answer = match serde_json::from_slice::<Message>(&message)
+            .map_err(Into::into)
+            .and_then(answer)
+        {
+            Ok(answer) => answer,
+            Err(err) => json!({ "error": err.to_string() }),
+        };
+        let answer = answer.to_string();
+        stdout.write_all(&(answer.len() as u32).to_ne_bytes())?;
+        stdout.write_all(answer.as_bytes())?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn answer(message: Message) -> Result<Value> {
+    match message {
+        Message::Add { title, url } => {
+            let title = title.trim();
+            if title.is_empty() {
+                bail!(tr!("No input given to add"));
+            }
+            // Everything after `--` is the description, so a page title can't set attributes
+            let uuid = create_task(vec!["add", "--", title]).context(tr!("adding task"))?;
+            if let Some(url) = url.filter(|url| !url.is_empty()) {
+                mutation::annotate(&uuid, &url)?;
+            }
+            Ok(json!({ "uuid": uuid }))
+        }
+        Message::Counts => Ok(json!({
+            "pending": query_sorted("status:pending")?.len(),
+            "due_today": query_sorted("status:pending due:today")?.len(),
+            "overdue": query_sorted("status:pending +OVERDUE")?.len(),
+        })),
+    }
+}
+
+/// The next message, or `None` once the browser has closed stdin.
+fn read_message(input: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match input.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE {
+        bail!(tr!("message of {} bytes is too large", length));
+    }
+    let mut message = vec![0; length];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
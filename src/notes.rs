@@ -0,0 +1,122 @@
+//! Long-form notes that don't fit in annotations: one Markdown file per task, named by uuid and
+//! linked from the task with a `file://` annotation so Open finds it too.
+//!
+//! ```toml
+//! [notes]
+//! dir = "~/notes/tasks"        # defaults to ~/.local/share/taskwarrior-rofi/notes
+//! command = "alacritty -e nvim {file}"   # defaults to the desktop's handler for .md
+//! ```
+//!
+//! With `[age] notes = true` new notes are encrypted as `<uuid>.md.age`. Opening one decrypts it
+//! to the runtime directory, waits for the command to exit and encrypts the result back, so a
+//! `command` that waits for the editor is required.
+
+use crate::{age, config::NotesConfig, mutation, paths, run_template};
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use task_hookrs::task::Task;
+
+pub fn open_notes(task: &Task, config: &NotesConfig) -> Result<()> {
+    let dir = notes_dir(config)?;
+    fs::create_dir_all(&dir).with_context(|| tr!("creating {}", dir.display()))?;
+    let encrypted = age::config().is_some_and(|age| age.notes);
+    let path = dir.join(if encrypted {
+        format!("{}.md.age", task.uuid())
+    } else {
+        format!("{}.md", task.uuid())
+    });
+    if !path.exists() {
+        let heading = format!("# {}\n\n", task.description());
+        let contents = if encrypted {
+            age::encrypt(heading.as_bytes(), false)?
+        } else {
+            heading.into_bytes()
+        };
+        fs::write(&path, contents).with_context(|| tr!("creating {}", path.display()))?;
+    }
+
+    let link = format!("file://{}", path.display());
+    let linked = task
+        .annotations()
+        .into_iter()
+        .flatten()
+        .any(|ann| *ann.description() == link);
+    if !linked {
+        mutation::annotate(&task.uuid().to_string(), &link)?;
+    }
+
+    open_file(&path, config)
+}
+
+/// Opens a notes file, through a decrypted copy for `.age` ones.
+pub fn open_file(path: &Path, config: &NotesConfig) -> Result<()> {
+    if path.extension().is_some_and(|ext| ext == "age") {
+        open_encrypted(path, config)
+    } else {
+        open(path, config)
+    }
+}
+
+fn open_encrypted(path: &Path, config: &NotesConfig) -> Result<()> {
+    let command = config.command.as_ref().ok_or_else(|| {
+        anyhow!(tr!(
+            "Encrypted notes need a [notes] command that waits for the editor to close"
+        ))
+    })?;
+    let plaintext =
+        age::decrypt(&fs::read(path).with_context(|| tr!("reading {}", path.display()))?)?;
+
+    let dir = paths::runtime_dir()?;
+    fs::create_dir_all(&dir)?;
+    let copy = dir.join(path.file_stem().expect("notes files have a name"));
+    write_private(&copy, &plaintext)?;
+    let result = run_template(command, "{file}", &copy.display().to_string()).and_then(|()| {
+        let edited = fs::read(&copy)?;
+        if edited != plaintext {
+            fs::write(path, age::encrypt(&edited, false)?)
+                .with_context(|| tr!("saving {}", path.display()))?;
+        }
+        Ok(())
+    });
+    // The plaintext goes whether or not the editor succeeded
+    let _ = fs::remove_file(&copy);
+    result
+}
+
+/// Writes a file only the user can read.
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .with_context(|| tr!("creating {}", path.display()))
+}
+
+fn notes_dir(config: &NotesConfig) -> Result<PathBuf> {
+    match &config.dir {
+        Some(dir) => paths::expand_home(dir),
+        None => paths::notes_dir(),
+    }
+}
+
+fn open(path: &Path, config: &NotesConfig) -> Result<()> {
+    let command = match &config.command {
+        Some(command) => command,
+        None => {
+            open::that(path).context(tr!("Could not open the notes file"))?;
+            return Ok(());
+        }
+    };
+
+    run_template(command, "{file}", &path.display().to_string())
+}
@@ -0,0 +1,115 @@
+//! Where every file lives. Each base directory follows the XDG spec and can be moved just for
+//! this tool with an environment variable:
+//!
+//! | base   | variable                       | default                             |
+//! |--------|--------------------------------|-------------------------------------|
+//! | config | `TASKWARRIOR_ROFI_CONFIG_DIR`  | `$XDG_CONFIG_HOME/taskwarrior-rofi` |
+//! | state  | `TASKWARRIOR_ROFI_STATE_DIR`   | `$XDG_STATE_HOME/taskwarrior-rofi`  |
+//! | data   | `TASKWARRIOR_ROFI_DATA_DIR`    | `$XDG_DATA_HOME/taskwarrior-rofi`   |
+//! | cache  | `TASKWARRIOR_ROFI_CACHE_DIR`   | `$XDG_CACHE_HOME/taskwarrior-rofi`  |
+//!
+//! with the XDG variables themselves defaulting to `~/.config`, `~/.local/state`,
+//! `~/.local/share` and `~/.cache`.
+
+use anyhow::{Context, Result};
+use std::{env, path::PathBuf};
+
+const APP: &str = "taskwarrior-rofi";
+
+pub fn home() -> Result<PathBuf> {
+    // Windows has USERPROFILE instead
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .context(tr!("HOME is not set"))
+}
+
+/// A configured path, with a leading `~/` meaning the home directory.
+pub fn expand_home(path: &str) -> Result<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(home()?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn base(override_var: &str, xdg_var: &str, fallback: &[&str]) -> Result<PathBuf> {
+    if let Some(dir) = env::var_os(override_var) {
+        return Ok(PathBuf::from(dir));
+    }
+    let xdg_home = match env::var_os(xdg_var) {
+        Some(dir) => PathBuf::from(dir),
+        None => fallback.iter().fold(home()?, |path, part| path.join(part)),
+    };
+    Ok(xdg_home.join(APP))
+}
+
+pub fn config_dir() -> Result<PathBuf> {
+    base(
+        "TASKWARRIOR_ROFI_CONFIG_DIR",
+        "XDG_CONFIG_HOME",
+        &[".config"],
+    )
+}
+
+pub fn state_dir() -> Result<PathBuf> {
+    base(
+        "TASKWARRIOR_ROFI_STATE_DIR",
+        "XDG_STATE_HOME",
+        &[".local", "state"],
+    )
+}
+
+pub fn data_dir() -> Result<PathBuf> {
+    base(
+        "TASKWARRIOR_ROFI_DATA_DIR",
+        "XDG_DATA_HOME",
+        &[".local", "share"],
+    )
+}
+
+pub fn cache_dir() -> Result<PathBuf> {
+    base("TASKWARRIOR_ROFI_CACHE_DIR", "XDG_CACHE_HOME", &[".cache"])
+}
+
+pub fn config_file() -> Result<PathBuf> {
+    Ok(config_dir()?.join("config.toml"))
+}
+
+pub fn locales_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join("locales"))
+}
+
+pub fn plugins_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join("plugins"))
+}
+
+#[cfg(feature = "scripting")]
+pub fn scripts_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join("scripts"))
+}
+
+pub fn state_file() -> Result<PathBuf> {
+    Ok(state_dir()?.join("state.json"))
+}
+
+pub fn backups_dir() -> Result<PathBuf> {
+    Ok(state_dir()?.join("backups"))
+}
+
+/// The runtime directory when there is one, which lives in memory and only as long as the login.
+pub fn runtime_dir() -> Result<PathBuf> {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => Ok(PathBuf::from(dir).join(APP)),
+        None => state_dir(),
+    }
+}
+
+/// Where `--watch` listens for pings.
+pub fn socket_file() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join("watch.sock"))
+}
+
+/// The default for `[notes] dir`.
+pub fn notes_dir() -> Result<PathBuf> {
+    Ok(data_dir()?.join("notes"))
+}
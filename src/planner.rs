@@ -0,0 +1,224 @@
+//! Greedy auto-scheduling: spreads the estimates of chosen tasks over the working days of a
+//! horizon, filling each day up to a daily capacity, and sets `scheduled` once the plan is
+//! confirmed.
+//!
+//! Estimates come from the `estimate` UDA, either a number of hours or a value like `90m`/`2h`.
+
+use crate::{
+    backup,
+    config::SchedulerConfig,
+    dates, default_tasks, duration, format_task, task_command, weeks,
+    widgets::{self, multi_select, rich_rofi, Row},
+    App, TaskExt,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{offset::Local as LocalTime, Duration, NaiveDate};
+use task_hookrs::task::Task;
+
+enum Horizon {
+    ThisWeek,
+    NextSevenDays,
+}
+
+struct Slot<'a> {
+    task: &'a Task,
+    hours: f64,
+    day: usize,
+}
+
+pub fn plan(app: &App) -> Result<()> {
+    let tasks = default_tasks(app)?;
+    let items = tasks
+        .iter()
+        .map(|task| Row::new(format_task(task, &app.config.style), task))
+        .collect();
+    let chosen = multi_select(&tr!("Tasks to schedule"), items)?;
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    let horizons = vec![
+        Row::new(tr!("Rest of this week"), Horizon::ThisWeek),
+        Row::new(tr!("Next 7 days"), Horizon::NextSevenDays),
+    ];
+    let days = working_days(rich_rofi(&tr!("Plan over"), horizons)?, app)?;
+    if days.is_empty() {
+        bail!(tr!("No working days left in that horizon"));
+    }
+
+    let config = &app.config.scheduler;
+    let slots = schedule(&chosen, days.len(), config);
+
+    let mut preview = vec![];
+    for (idx, day) in days.iter().enumerate() {
+        let booked: f64 = slots.iter().filter(|s| s.day == idx).map(|s| s.hours).sum();
+        preview.push(format!(
+            "{} ({:.1}h of {:.1}h)",
+            day.format("%a %Y-%m-%d"),
+            booked,
+            config.daily_capacity
+        ));
+        preview.extend(slots.iter().filter(|s| s.day == idx).map(|slot| {
+            format!(
+                "    {} ({:.1}h)",
+                format_task(slot.task, &app.config.style),
+                slot.hours
+            )
+        }));
+    }
+    if !widgets::confirm(
+        &tr!("Apply this plan?"),
+        &tr!("Apply"),
+        &tr!("Cancel"),
+        preview,
+    )? {
+        return Ok(());
+    }
+
+    let uuids: Vec<_> = slots.iter().map(|s| s.task.uuid().to_string()).collect();
+    backup::snapshot(
+        &uuids.iter().map(String::as_str).collect::<Vec<_>>(),
+        "plan",
+    )?;
+    for (idx, day) in days.iter().enumerate() {
+        let uuids: Vec<_> = slots
+            .iter()
+            .filter(|s| s.day == idx)
+            .map(|s| s.task.uuid().to_string())
+            .collect();
+        if uuids.is_empty() {
+            continue;
+        }
+        let scheduled = format!("scheduled:{}", day.format("%Y-%m-%d"));
+        let mut args = vec!["rc.confirmation=off", "rc.bulk=0"];
+        args.extend(uuids.iter().map(String::as_str));
+        args.extend(vec!["mod", &scheduled]);
+        task_command(args).context(tr!("scheduling tasks"))?;
+    }
+
+    Ok(())
+}
+
+/// Places each task, in order, on the first day with room for it, or on the least booked day
+/// when nothing has room.
+fn schedule<'a>(tasks: &[&'a Task], days: usize, config: &SchedulerConfig) -> Vec<Slot<'a>> {
+    let mut booked = vec![0.0; days];
+    let mut slots = vec![];
+
+    for task in tasks {
+        let hours = estimate_hours(task).unwrap_or(config.default_estimate);
+        let day = booked
+            .iter()
+            .position(|b| b + hours <= config.daily_capacity)
+            .unwrap_or_else(|| {
+                (0..days)
+                    .min_by(|a, b| booked[*a].partial_cmp(&booked[*b]).unwrap())
+                    .unwrap_or(0)
+            });
+        booked[day] += hours;
+        slots.push(Slot { task, hours, day });
+    }
+
+    slots
+}
+
+pub fn estimate_hours(task: &Task) -> Option<f64> {
+    let estimate = task.uda_string("estimate")?;
+    // A bare number is hours, anything else goes through the shared duration parser
+    estimate.trim().parse().ok().or_else(|| {
+        duration::parse(&estimate)
+            .ok()
+            .map(|duration| duration.num_seconds() as f64 / 3600.0)
+    })
+}
+
+fn working_days(horizon: Horizon, app: &App) -> Result<Vec<NaiveDate>> {
+    let today = LocalTime::now().naive_local().date();
+    let last = match horizon {
+        Horizon::ThisWeek => weeks::end_of_week(today, weeks::first_day()),
+        Horizon::NextSevenDays => today + Duration::days(7),
+    };
+
+    let holidays = dates::holidays(&app.config)?;
+    let mut days = vec![];
+    let mut day = today;
+    while day <= last {
+        if !dates::is_weekend(day) && !holidays.contains(&day) {
+            days.push(day);
+        }
+        day += Duration::days(1);
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(estimate: Option<&str>) -> Task {
+        let mut task = json!({
+            "uuid": "8ca953d5-18b6-4eb1-a2b3-f38f1b6d8b9a",
+            "description": "Write the report",
+            "status": "pending",
+            "entry": "20261014T090000Z",
+        });
+        if let Some(estimate) = estimate {
+            task["estimate"] = json!(estimate);
+        }
+        serde_json::from_value(task).unwrap()
+    }
+
+    fn config() -> SchedulerConfig {
+        SchedulerConfig {
+            daily_capacity: 4.0,
+            default_estimate: 1.0,
+        }
+    }
+
+    fn days(tasks: &[Task], days: usize) -> Vec<usize> {
+        let tasks: Vec<_> = tasks.iter().collect();
+        schedule(&tasks, days, &config())
+            .iter()
+            .map(|slot| slot.day)
+            .collect()
+    }
+
+    #[test]
+    fn fills_a_day_before_the_next() {
+        let tasks = [task(Some("2h")), task(Some("2h")), task(Some("1h"))];
+        assert_eq!(days(&tasks, 3), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn later_tasks_fill_gaps_left_earlier() {
+        let tasks = [task(Some("3h")), task(Some("3h")), task(Some("1h"))];
+        assert_eq!(days(&tasks, 2), vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn overflow_goes_on_the_least_booked_day() {
+        let tasks = [task(Some("4h")), task(Some("3h")), task(Some("2h"))];
+        assert_eq!(days(&tasks, 2), vec![0, 1, 1]);
+        let tasks = [task(Some("5h"))];
+        assert_eq!(days(&tasks, 2), vec![0]);
+    }
+
+    #[test]
+    fn unestimated_tasks_take_the_default() {
+        let tasks = [task(None), task(Some("3h")), task(None)];
+        let tasks: Vec<_> = tasks.iter().collect();
+        let slots = schedule(&tasks, 2, &config());
+        let hours: Vec<_> = slots.iter().map(|slot| slot.hours).collect();
+        assert_eq!(hours, vec![1.0, 3.0, 1.0]);
+        assert_eq!(slots[2].day, 1);
+    }
+
+    #[test]
+    fn estimates() {
+        assert_eq!(estimate_hours(&task(Some("1.5"))), Some(1.5));
+        assert_eq!(estimate_hours(&task(Some("90m"))), Some(1.5));
+        assert_eq!(estimate_hours(&task(Some("soon"))), None);
+        assert_eq!(estimate_hours(&task(None)), None);
+    }
+}
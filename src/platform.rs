@@ -0,0 +1,34 @@
+//! What differs between Linux, macOS and Windows, besides the launcher (see `launcher`): which
+//! program takes the clipboard and what counts as executable. Opening links goes through the
+//! `open` crate, which already knows each desktop's handler.
+
+use std::{env, path::Path, process::Command};
+
+/// The command that puts its stdin on the clipboard.
+pub fn clipboard() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("pbcopy")
+    } else if cfg!(windows) {
+        Command::new("clip")
+    } else if env::var_os("WAYLAND_DISPLAY").is_some() {
+        Command::new("wl-copy")
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard"]);
+        command
+    }
+}
+
+/// Whether a plugin file can be run. Windows has no execute bit, so any file counts there.
+pub fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
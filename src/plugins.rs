@@ -0,0 +1,148 @@
+//! Drop-in plugins.
+//!
+//! Every executable file in `~/.config/taskwarrior-rofi/plugins/` is listed in the action menu
+//! under its file name. Choosing one asks for a task, then runs the plugin with that task's JSON
+//! (the same shape `task export` produces) on stdin.
+//!
+//! A plugin may print a follow-up menu on stdout as a single JSON object:
+//!
+//! ```json
+//! {"prompt": "Pick one", "items": [{"label": "Shown in rofi", "value": "handed back"}]}
+//! ```
+//!
+//! Items may also set `icon` (an icon name, shown when rofi's `show-icons` is on) and `active` or
+//! `urgent` to highlight them.
+//!
+//! `prompt` defaults to the plugin's name and an item's `value` defaults to its `label`. When the
+//! user picks an item, the plugin is run again with the same stdin and the chosen value in the
+//! `TASKWARRIOR_ROFI_SELECTION` environment variable, so it can chain as many menus as it likes.
+//! Printing nothing ends the interaction. A non-zero exit status is shown as an error along with
+//! whatever the plugin wrote to stderr.
+
+use crate::{
+    paths, platform,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use task_hookrs::task::Task;
+
+#[derive(Clone)]
+pub struct Plugin {
+    pub name: String,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Menu {
+    prompt: Option<String>,
+    items: Vec<MenuItem>,
+}
+
+#[derive(Deserialize)]
+struct MenuItem {
+    label: String,
+    value: Option<String>,
+    icon: Option<String>,
+    #[serde(default)]
+    active: bool,
+    #[serde(default)]
+    urgent: bool,
+}
+
+impl Plugin {
+    pub fn discover() -> Result<Vec<Self>> {
+        let dir = paths::plugins_dir()?;
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut plugins = vec![];
+        for entry in fs::read_dir(&dir).with_context(|| tr!("reading {}", dir.display()))? {
+            let path = entry?.path();
+            if platform::is_executable(&path) {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                plugins.push(Self { name, path });
+            }
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(plugins)
+    }
+
+    pub fn run(&self, task: &Task) -> Result<()> {
+        let input = serde_json::to_string(task)?;
+        let mut selection: Option<String> = None;
+
+        loop {
+            let output = self.invoke(&input, selection.as_deref())?;
+            if output.trim().is_empty() {
+                return Ok(());
+            }
+
+            let menu: Menu = serde_json::from_str(&output)
+                .with_context(|| tr!("plugin {} printed an invalid menu", self.name))?;
+            let items: Vec<_> = menu
+                .items
+                .into_iter()
+                .map(|menu_item| {
+                    let MenuItem {
+                        label,
+                        value,
+                        icon,
+                        active,
+                        urgent,
+                    } = menu_item;
+                    let item = value.unwrap_or_else(|| label.clone());
+                    let row = Row::new(label, item).active(active).urgent(urgent);
+                    match icon {
+                        Some(icon) => row.icon(&icon),
+                        None => row,
+                    }
+                })
+                .collect();
+            let prompt = menu.prompt.unwrap_or_else(|| self.name.clone());
+            selection = Some(rich_rofi(&prompt, items)?);
+        }
+    }
+
+    fn invoke(&self, input: &str, selection: Option<&str>) -> Result<String> {
+        let mut command = Command::new(&self.path);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(selection) = selection {
+            command.env("TASKWARRIOR_ROFI_SELECTION", selection);
+        }
+
+        let mut child = command
+            .spawn()
+            .with_context(|| tr!("running plugin {}", self.name))?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(input.as_bytes())?;
+        let result = child.wait_with_output()?;
+
+        if !result.status.success() {
+            bail!(
+                "plugin {} failed: {}",
+                self.name,
+                String::from_utf8_lossy(&result.stderr)
+            );
+        }
+
+        Ok(String::from_utf8(result.stdout)?)
+    }
+}
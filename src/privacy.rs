@@ -0,0 +1,80 @@
+//! Private annotations, marked with a prefix (`private:` unless `[privacy] prefix` says
+//! otherwise), are masked wherever annotations are shown or shared until Unlock is picked, so
+//! they don't end up on a shared screen. Unlocking lasts until the menu closes.
+//!
+//! Private annotations encrypted with `age` are decrypted for showing once unlocked.
+
+use crate::{age, config::PrivacyConfig};
+use anyhow::Result;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    OnceLock,
+};
+use task_hookrs::{annotation::Annotation, task::Task};
+
+const MASK: &str = "••••••";
+
+static PREFIX: OnceLock<String> = OnceLock::new();
+static UNLOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Called for every menu, so `--watch` starts each one locked again.
+pub fn init(config: &PrivacyConfig) {
+    let _ = PREFIX.set(config.prefix.clone());
+    UNLOCKED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_private(annotation: &Annotation) -> bool {
+    PREFIX
+        .get()
+        .is_some_and(|prefix| !prefix.is_empty() && annotation.description().starts_with(prefix))
+}
+
+pub fn unlocked() -> bool {
+    UNLOCKED.load(Ordering::Relaxed)
+}
+
+pub fn toggle_lock() {
+    UNLOCKED.fetch_xor(true, Ordering::Relaxed);
+}
+
+/// The annotation's text as it may be shown right now.
+pub fn shown(annotation: &Annotation) -> String {
+    if !is_private(annotation) {
+        return annotation.description().to_string();
+    }
+    let prefix = PREFIX.get().expect("private needs a prefix");
+    if !unlocked() {
+        return format!("{}{}", prefix, MASK);
+    }
+    let text = &annotation.description()[prefix.len()..];
+    match text.strip_prefix(age::ANNOTATION_MARKER) {
+        // Left encrypted when it doesn't decrypt, rather than failing the whole menu
+        Some(ciphertext) => age::decrypt_line(ciphertext)
+            .map(|plaintext| format!("{}{}", prefix, plaintext))
+            .unwrap_or_else(|_| annotation.description().to_string()),
+        None => annotation.description().to_string(),
+    }
+}
+
+/// Encrypts the text of a private annotation about to be added; others are left alone.
+pub fn encrypt(text: &str, config: &PrivacyConfig) -> Result<String> {
+    match text.strip_prefix(&config.prefix) {
+        Some(private) if !config.prefix.is_empty() => Ok(format!(
+            "{}{}{}",
+            config.prefix,
+            age::ANNOTATION_MARKER,
+            age::encrypt_line(private.trim_start())?
+        )),
+        _ => Ok(text.to_string()),
+    }
+}
+
+/// A copy of the task to share or export, with private annotations masked while locked.
+pub fn masked(task: &Task) -> Task {
+    let mut task = task.clone();
+    for annotation in task.annotations_mut().into_iter().flatten() {
+        let text = shown(annotation);
+        *annotation.description_mut() = text;
+    }
+    task
+}
@@ -0,0 +1,227 @@
+//! What the environment can do: whether `task` and `rofi` are installed and which taskwarrior
+//! version is there. Startup checks the essentials; `taskwarrior-rofi doctor` goes through
+//! everything and suggests fixes.
+//!
+//! Differences between taskwarrior versions live in `Capabilities`, probed once per run. Config
+//! lookups use `_get`, which both 2.x and 3.x support. Recurrence needs no special handling:
+//! both keep templates in `status:recurring`, which the filters here already leave out.
+
+use crate::{config::Config, get_config_var, paths};
+use anyhow::{anyhow, bail, Context, Result};
+use std::{env, process::Command, sync::OnceLock};
+
+/// Older versions lack `export` filters and the `_show` helper this relies on.
+const MIN_TASK_VERSION: (u32, u32, u32) = (2, 5, 0);
+
+/// UDAs that features need, with the feature they belong to and the taskrc line defining them.
+pub const FEATURE_UDAS: [(&str, &str, &str); 5] = [
+    ("estimate", "Estimate, Plan", "uda.estimate.type=string"),
+    ("kanban", "Board", "uda.kanban.type=string"),
+    (
+        "energy",
+        "Energy, What can I do now?",
+        "uda.energy.type=string",
+    ),
+    ("location", "Here", "uda.location.type=string"),
+    ("phone", "Call", "uda.phone.type=string"),
+];
+
+/// What the installed taskwarrior supports.
+pub struct Capabilities {
+    pub version: (u32, u32, u32),
+}
+
+impl Capabilities {
+    /// Arguments that make `export` print a JSON array, which is only the default from 2.6 on.
+    pub fn export_args(&self) -> Vec<&'static str> {
+        if self.version < (2, 6, 0) {
+            vec!["rc.json.array=on", "export"]
+        } else {
+            vec!["export"]
+        }
+    }
+
+    /// Whether `task sync` has somewhere to sync to. 3.x replaced taskd with its own servers.
+    pub fn sync_configured(&self) -> bool {
+        let settings: &[&str] = if self.version.0 >= 3 {
+            &[
+                "sync.server.url",
+                "sync.server.origin",
+                "sync.local.server_dir",
+                "sync.gcp.bucket",
+                "sync.aws.bucket",
+            ]
+        } else {
+            &["taskd.server"]
+        };
+        settings
+            .iter()
+            .any(|setting| get_config_var(setting).is_ok())
+    }
+}
+
+/// What to set up for `task sync` to have a server.
+pub fn sync_fix() -> String {
+    if capabilities().version.0 >= 3 {
+        tr!("Set sync.server.url (or another sync.* backend) in taskrc to use sync")
+    } else {
+        tr!("Set taskd.server in taskrc to use sync")
+    }
+}
+
+/// The capabilities of the installed taskwarrior, probed on first use. If the version can't be
+/// read this assumes the oldest supported one; startup has already complained by then.
+pub fn capabilities() -> &'static Capabilities {
+    static CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+    CAPABILITIES.get_or_init(|| Capabilities {
+        version: task_version().unwrap_or(MIN_TASK_VERSION),
+    })
+}
+
+pub fn task_version() -> Result<(u32, u32, u32)> {
+    let output = Command::new("task")
+        .arg("--version")
+        .output()
+        .context(tr!("running task, is taskwarrior installed?"))?;
+    let version = String::from_utf8_lossy(&output.stdout);
+    parse_version(version.trim())
+        .ok_or_else(|| anyhow!(tr!("couldn't read the version from `{}`", version.trim())))
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split('.').map(|part| {
+        part.chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .ok()
+    });
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+pub fn on_path(program: &str) -> bool {
+    let names = if cfg!(windows) {
+        vec![format!("{}.exe", program), format!("{}.cmd", program)]
+    } else {
+        vec![program.to_string()]
+    };
+    env::var_os("PATH").is_some_and(|path| {
+        env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+    })
+}
+
+/// The checks every run needs to get as far as showing a menu.
+pub fn check_startup() -> Result<()> {
+    let version = task_version()?;
+    if version < MIN_TASK_VERSION {
+        bail!(tr!(
+            "taskwarrior {} is too old, {} or newer is needed",
+            format_version(version),
+            format_version(MIN_TASK_VERSION)
+        ));
+    }
+    Ok(())
+}
+
+/// Prints a line per check, returning whether all the required ones passed.
+pub fn doctor() -> bool {
+    let mut healthy = true;
+    let mut report = |ok: bool, required: bool, what: String, fix: String| {
+        let mark = match (ok, required) {
+            (true, _) => "ok  ",
+            (false, true) => "FAIL",
+            (false, false) => "warn",
+        };
+        println!("[{}] {}", mark, what);
+        if !ok {
+            println!("       {}", fix);
+            healthy &= !required;
+        }
+    };
+
+    match task_version() {
+        Ok(version) => report(
+            version >= MIN_TASK_VERSION,
+            true,
+            tr!("taskwarrior {}", format_version(version)),
+            tr!(
+                "Upgrade to taskwarrior {} or newer",
+                format_version(MIN_TASK_VERSION)
+            ),
+        ),
+        Err(err) => report(
+            false,
+            true,
+            tr!("taskwarrior: {}", err),
+            tr!("Install taskwarrior and make sure `task` is on PATH"),
+        ),
+    }
+
+    report(
+        capabilities().sync_configured(),
+        false,
+        tr!("sync server"),
+        sync_fix(),
+    );
+
+    let launcher = crate::launcher::current().name();
+    report(
+        launcher == "terminal" || on_path(launcher),
+        true,
+        tr!("launcher: {}", launcher),
+        tr!(
+            "Install {} and make sure it is on PATH, or set launcher in the config",
+            launcher
+        ),
+    );
+
+    let report_filter = get_config_var("default.command")
+        .and_then(|command| get_config_var(&format!("report.{}.filter", command)));
+    report(
+        report_filter.is_ok(),
+        true,
+        tr!("default report filter"),
+        tr!("Set default.command in taskrc to a report with a filter, like `next`"),
+    );
+
+    match Config::load() {
+        Ok(config) => {
+            report(true, true, tr!("config file"), String::new());
+            report(
+                get_config_var(&format!("uda.{}.type", config.vault.uda)).is_ok()
+                    || config.vault.path.is_none(),
+                false,
+                tr!("{} UDA (Link note)", config.vault.uda),
+                tr!("Add uda.{}.type=string to taskrc", config.vault.uda),
+            );
+        }
+        Err(err) => report(
+            false,
+            true,
+            tr!("config file: {}", format!("{:#}", err)),
+            match paths::config_file() {
+                Ok(path) => tr!("Fix or remove {}", path.display()),
+                Err(err) => err.to_string(),
+            },
+        ),
+    }
+
+    for (uda, feature, line) in FEATURE_UDAS.iter() {
+        report(
+            get_config_var(&format!("uda.{}.type", uda)).is_ok(),
+            false,
+            tr!("{} UDA ({})", uda, feature),
+            tr!("Add {} to taskrc", line),
+        );
+    }
+
+    healthy
+}
+
+fn format_version((major, minor, patch): (u32, u32, u32)) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
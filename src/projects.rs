@@ -0,0 +1,75 @@
+//! Per-project progress, like `task projects` but with completion and drill-down into a project's
+//! pending tasks.
+
+use crate::{
+    back_on_escape, export, query_sorted, quick,
+    style::{Glyph, Style},
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+const BAR_WIDTH: usize = 10;
+
+#[derive(Default)]
+struct Counts {
+    pending: usize,
+    completed: usize,
+}
+
+pub fn project_status(app: &App) -> Result<()> {
+    loop {
+        let mut projects: BTreeMap<String, Counts> = BTreeMap::new();
+        for task in export::query("status:pending")? {
+            if let Some(project) = task.project() {
+                projects.entry(project.clone()).or_default().pending += 1;
+            }
+        }
+        for task in export::query("status:completed")? {
+            if let Some(project) = task.project() {
+                projects.entry(project.clone()).or_default().completed += 1;
+            }
+        }
+
+        // Projects with nothing left to do are finished, not worth a row
+        projects.retain(|_, counts| counts.pending > 0);
+        let width = projects.keys().map(String::len).max().unwrap_or(0);
+        let rows = projects
+            .iter()
+            .map(|(project, counts)| {
+                Row::new(
+                    format!(
+                        "{:<width$}  {}  {}/{}",
+                        project,
+                        progress_bar(counts, &app.config.style),
+                        counts.completed,
+                        counts.pending + counts.completed,
+                        width = width
+                    ),
+                    project.clone(),
+                )
+            })
+            .collect();
+
+        let project: String = rich_rofi(&tr!("Project status"), rows)?;
+        // Escaping a project's tasks goes back to the projects
+        back_on_escape(project_tasks(&project, &app.config.style))?;
+    }
+}
+
+fn project_tasks(project: &str, style: &Style) -> Result<()> {
+    let tasks = query_sorted(&format!("status:pending project:{}", project))?;
+    quick::pick_and_act(project, tasks, style)
+}
+
+fn progress_bar(counts: &Counts, style: &Style) -> String {
+    let total = counts.pending + counts.completed;
+    let filled = (counts.completed * BAR_WIDTH + total / 2) / total;
+    format!(
+        "{}{} {:>3}%",
+        style.glyph(Glyph::BarFull).repeat(filled),
+        style.glyph(Glyph::BarEmpty).repeat(BAR_WIDTH - filled),
+        counts.completed * 100 / total
+    )
+}
@@ -0,0 +1,55 @@
+//! The short list-then-Start/Done menu that several views end with.
+
+use crate::{
+    conflict, format_task, recurrence, related,
+    style::Style,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::Result;
+use chrono::offset::Local as LocalTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+enum Choice {
+    Start,
+    Done,
+    Related,
+    Back,
+}
+
+/// Called for every menu. Read-only menus still end views here, but without Start and Done.
+pub fn init(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+/// Picks one of `tasks` and starts or finishes it.
+pub fn pick_and_act(prompt: &str, tasks: Vec<Task>, style: &Style) -> Result<()> {
+    let rows = tasks
+        .into_iter()
+        .map(|task| Row::new(format_task(&task, style), task))
+        .collect();
+    let picked: Task = rich_rofi(prompt, rows)?;
+    act(&recurrence::guard(picked)?, style)
+}
+
+/// Starts or finishes one task, or moves on to the tasks like it.
+pub fn act(picked: &Task, style: &Style) -> Result<()> {
+    let mut task = picked.clone();
+    let mut choices = vec![];
+    if !READ_ONLY.load(Ordering::Relaxed) {
+        choices.push(Row::new(tr!("Start"), Choice::Start));
+        choices.push(Row::new(tr!("Done"), Choice::Done));
+    }
+    choices.push(Row::new(tr!("Show related"), Choice::Related));
+    choices.push(Row::new(tr!("Back"), Choice::Back));
+    match rich_rofi(task.description(), choices)? {
+        Choice::Start => task.set_start(Some(LocalTime::now().naive_local())),
+        Choice::Done => *task.status_mut() = TaskStatus::Completed,
+        Choice::Related => return related::show(picked, style),
+        Choice::Back => return Ok(()),
+    }
+    conflict::save(picked, &task, false)?;
+    Ok(())
+}
@@ -0,0 +1,293 @@
+//! A look at the due dates a `recur:` would generate, asked before Add or Mod saves it, so that
+//! mistakes like `recur:monthly due:31st` show up while they are still easy to fix.
+//!
+//! The dates follow taskwarrior's rules: each one comes from the one before it, and a day the
+//! month doesn't have becomes its last day. That is why the 31st drifts to the 28th after
+//! February and stays there.
+//!
+//! Picking the template of a recurring task (status `recurring`) is almost always a mistake:
+//! finishing it stops the recurrence and starting it starts no instance. `guard` offers the next
+//! pending instance instead.
+
+use crate::{
+    dates, export,
+    widgets::{self, rich_rofi, Row},
+};
+use anyhow::{bail, Result};
+use chrono::{
+    offset::Local as LocalTime, Datelike, Duration, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+const OCCURRENCES: usize = 5;
+
+enum Period {
+    Every(Duration),
+    Weekdays,
+    Months(i32),
+}
+
+/// Shows the next occurrences when `args` set `recur:` and asks whether to go ahead. `due` is the
+/// task's current due date (local time), used when the args don't set one. Anything there is no
+/// preview for, including periods taskwarrior will reject anyway, goes ahead without asking.
+pub fn confirm<'a, I>(args: I, due: Option<NaiveDateTime>) -> Result<bool>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut recur = None;
+    let mut due_arg = None;
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("recur:") {
+            recur = Some(value);
+        } else if let Some(value) = arg.strip_prefix("due:") {
+            due_arg = Some(value);
+        }
+    }
+
+    let recur = match recur.filter(|recur| !recur.is_empty()) {
+        Some(recur) => recur,
+        None => return Ok(true),
+    };
+    let period = match parse(recur) {
+        Some(period) => period,
+        None => return Ok(true),
+    };
+    let due = match due_arg {
+        Some(value) => dates::calc(value)?,
+        None => due,
+    };
+    let due = match due {
+        Some(due) => due,
+        None => return Ok(true),
+    };
+
+    let preview = occurrences(due, &period)
+        .into_iter()
+        .map(|date| {
+            let label = format_date(date);
+            if matches!(period, Period::Months(_)) && date.day() != due.day() {
+                tr!("{} (not day {} any more)", label, due.day())
+            } else {
+                label
+            }
+        })
+        .collect();
+    Ok(widgets::confirm(
+        &tr!("recur:{} from {}", recur, format_date(due)),
+        &tr!("Save"),
+        &tr!("Cancel"),
+        preview,
+    )?)
+}
+
+/// The task to act on in place of `task`: the task itself unless it is a recurrence template, and
+/// otherwise whichever of its next instance or the template itself is chosen.
+pub fn guard(task: Task) -> Result<Task> {
+    if *task.status() != TaskStatus::Recurring {
+        return Ok(task);
+    }
+    let next = next_instance(&task)?;
+    let prompt = tr!("{} is the template of a recurring task", task.description());
+    let mut rows = vec![];
+    match &next {
+        Some(instance) => {
+            let label = match instance.due() {
+                Some(due) => tr!(
+                    "Use the next instance (due {})",
+                    format_date(LocalTime.from_utc_datetime(due).naive_local())
+                ),
+                None => tr!("Use the next instance"),
+            };
+            rows.push(Row::new(label, true));
+        }
+        None => rows.push(Row::new(tr!("No pending instance, cancel"), true)),
+    }
+    rows.push(Row::new(tr!("Use the template anyway"), false));
+    let use_instance: bool = rich_rofi(&prompt, rows)?;
+    match (use_instance, next) {
+        (true, Some(instance)) => Ok(instance),
+        (true, None) => bail!(tr!("{} has no pending instance", task.description())),
+        (false, _) => Ok(task),
+    }
+}
+
+/// The pending child of a recurrence template that is due soonest.
+fn next_instance(parent: &Task) -> Result<Option<Task>> {
+    let filter = format!("parent:{} status:pending", parent.uuid());
+    let mut soonest: Option<Task> = None;
+    for task in export::stream(&filter)? {
+        let task = task?;
+        let sooner = match (&soonest, task.due()) {
+            (None, _) => true,
+            (Some(current), Some(due)) => current.due().is_none_or(|current| **due < **current),
+            (Some(_), None) => false,
+        };
+        if sooner {
+            soonest = Some(task);
+        }
+    }
+    Ok(soonest)
+}
+
+/// The periods taskwarrior treats specially (months, quarters, years and weekdays) and fixed
+/// lengths like `3d` or `2wk`.
+fn parse(recur: &str) -> Option<Period> {
+    let recur = recur.to_lowercase();
+    let period = match recur.as_str() {
+        "weekdays" => Period::Weekdays,
+        "daily" | "day" => Period::Every(Duration::days(1)),
+        "weekly" | "week" | "sennight" => Period::Every(Duration::weeks(1)),
+        "biweekly" | "fortnight" => Period::Every(Duration::weeks(2)),
+        "monthly" | "month" => Period::Months(1),
+        "bimonthly" => Period::Months(2),
+        "quarterly" | "quarter" => Period::Months(3),
+        "semiannual" => Period::Months(6),
+        "annual" | "yearly" | "year" => Period::Months(12),
+        "biannual" | "biyearly" => Period::Months(24),
+        _ => {
+            let split = recur
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(recur.len());
+            let count = match &recur[..split] {
+                "" => 1,
+                count => count.parse().ok()?,
+            };
+            match &recur[split..] {
+                "h" | "hr" | "hrs" | "hour" | "hours" => Period::Every(Duration::hours(count)),
+                "d" | "day" | "days" => Period::Every(Duration::days(count)),
+                "w" | "wk" | "wks" | "week" | "weeks" => Period::Every(Duration::weeks(count)),
+                "mo" | "mos" | "mth" | "mths" | "month" | "months" => Period::Months(count as i32),
+                "q" | "qtr" | "qtrs" | "quarter" | "quarters" => Period::Months(count as i32 * 3),
+                "y" | "yr" | "yrs" | "year" | "years" => Period::Months(count as i32 * 12),
+                _ => return None,
+            }
+        }
+    };
+    match period {
+        Period::Every(length) if length <= Duration::zero() => None,
+        Period::Months(months) if months < 1 => None,
+        period => Some(period),
+    }
+}
+
+/// The `OCCURRENCES` due dates after `due`.
+fn occurrences(due: NaiveDateTime, period: &Period) -> Vec<NaiveDateTime> {
+    let mut dates = vec![];
+    let mut date = due;
+    for _ in 0..OCCURRENCES {
+        date = match period {
+            Period::Every(length) => date + *length,
+            Period::Weekdays => {
+                let days = match date.weekday() {
+                    Weekday::Fri => 3,
+                    Weekday::Sat => 2,
+                    _ => 1,
+                };
+                date + Duration::days(days)
+            }
+            Period::Months(months) => {
+                dates::shift_months(date.date(), *months).and_time(date.time())
+            }
+        };
+        dates.push(date);
+    }
+    dates
+}
+
+fn format_date(date: NaiveDateTime) -> String {
+    if date.time() == NaiveTime::from_hms(0, 0, 0) {
+        date.format("%a %Y-%m-%d").to_string()
+    } else {
+        date.format("%a %Y-%m-%d %H:%M").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn every(recur: &str) -> Option<Duration> {
+        match parse(recur)? {
+            Period::Every(length) => Some(length),
+            _ => None,
+        }
+    }
+
+    fn months(recur: &str) -> Option<i32> {
+        match parse(recur)? {
+            Period::Months(months) => Some(months),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn named_periods() {
+        assert!(matches!(parse("weekdays"), Some(Period::Weekdays)));
+        assert_eq!(every("daily"), Some(Duration::days(1)));
+        assert_eq!(every("Fortnight"), Some(Duration::weeks(2)));
+        assert_eq!(months("monthly"), Some(1));
+        assert_eq!(months("quarterly"), Some(3));
+        assert_eq!(months("biannual"), Some(24));
+    }
+
+    #[test]
+    fn counted_periods() {
+        assert_eq!(every("3d"), Some(Duration::days(3)));
+        assert_eq!(every("2wk"), Some(Duration::weeks(2)));
+        assert_eq!(every("hour"), Some(Duration::hours(1)));
+        assert_eq!(months("2q"), Some(6));
+        assert_eq!(months("1y"), Some(12));
+    }
+
+    #[test]
+    fn rejected_periods() {
+        assert!(parse("0d").is_none());
+        assert!(parse("0mo").is_none());
+        assert!(parse("3x").is_none());
+        assert!(parse("fortnightly").is_none());
+    }
+
+    #[test]
+    fn fixed_lengths_add_up() {
+        let dates = occurrences(date(2026, 10, 14), &Period::Every(Duration::days(3)));
+        assert_eq!(dates[0], date(2026, 10, 17));
+        assert_eq!(dates[4], date(2026, 10, 29));
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        let dates = occurrences(date(2026, 10, 15), &Period::Weekdays);
+        let days: Vec<_> = dates.iter().map(|date| date.day()).collect();
+        assert_eq!(days, vec![16, 19, 20, 21, 22]);
+    }
+
+    #[test]
+    fn months_drift_to_the_shortest_month() {
+        let dates = occurrences(date(2026, 1, 31), &Period::Months(1));
+        assert_eq!(
+            dates,
+            vec![
+                date(2026, 2, 28),
+                date(2026, 3, 28),
+                date(2026, 4, 28),
+                date(2026, 5, 28),
+                date(2026, 6, 28),
+            ]
+        );
+    }
+
+    #[test]
+    fn occurrences_keep_the_time() {
+        let due = date(2026, 10, 14) + Duration::hours(9);
+        let dates = occurrences(due, &Period::Months(12));
+        assert_eq!(dates[0], date(2027, 10, 14) + Duration::hours(9));
+    }
+}
@@ -0,0 +1,47 @@
+//! Show related, from a task's Start/Done menu: the other pending tasks that share its project or
+//! any of its tags, most alike first, for batching similar work.
+//!
+//! Each shared tag counts one and the same project counts two; a project only sharing the top
+//! level, like `home.garden` and `home.kitchen`, counts one. Equally alike tasks stay in urgency
+//! order.
+
+use crate::{query_sorted, quick, style::Style};
+use anyhow::{bail, Result};
+use task_hookrs::task::Task;
+
+pub fn show(task: &Task, style: &Style) -> Result<()> {
+    let mut scored: Vec<_> = query_sorted("status:pending")?
+        .into_iter()
+        .filter(|other| other.uuid() != task.uuid())
+        .map(|other| (overlap(task, &other), other))
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    if scored.is_empty() {
+        bail!(tr!(
+            "No pending tasks share a project or tag with {}",
+            task.description()
+        ));
+    }
+    // Stable, so ties keep the urgency order of the query
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let tasks = scored.into_iter().map(|(_, other)| other).collect();
+    quick::pick_and_act(&tr!("Related to {}", task.description()), tasks, style)
+}
+
+/// How much two tasks have in common.
+fn overlap(a: &Task, b: &Task) -> usize {
+    let project = match (a.project(), b.project()) {
+        (Some(a), Some(b)) if a == b => 2,
+        (Some(a), Some(b)) if top_level(a) == top_level(b) => 1,
+        _ => 0,
+    };
+    let tags = match (a.tags(), b.tags()) {
+        (Some(a), Some(b)) => a.iter().filter(|tag| b.contains(tag)).count(),
+        _ => 0,
+    };
+    project + tags
+}
+
+fn top_level(project: &str) -> &str {
+    project.split('.').next().unwrap_or(project)
+}
@@ -0,0 +1,171 @@
+//! `taskwarrior-rofi remind`, meant to run from a timer: a desktop notification listing old
+//! tasks, with a `Review old tasks` action that opens them in the stalled view.
+//!
+//! ```ini
+//! # ~/.config/systemd/user/taskwarrior-rofi-remind.timer
+//! [Timer]
+//! OnCalendar=Mon 09:00
+//! ```
+//!
+//! With `[remind] per_task = true` each old task gets a notification of its own instead, with
+//! actions to snooze it for an hour or a day. Snoozed tasks are left out until the snooze ends.
+//!
+//! During quiet hours the notification waits until they are over, or is dropped:
+//!
+//! ```toml
+//! [quiet]
+//! drop = false
+//!
+//! [[quiet.hours]]
+//! from = "22:00"
+//! until = "08:00"
+//!
+//! [[quiet.hours]]
+//! days = ["sat", "sun"]
+//! ```
+
+use crate::{back_on_escape, config::QuietConfig, stalled, App};
+use anyhow::{bail, Context, Result};
+use chrono::{offset::Local as LocalTime, Duration, NaiveDateTime};
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+use task_hookrs::task::Task;
+
+/// How many descriptions the notification lists before "and N more".
+const DIGEST_LENGTH: usize = 5;
+/// Quiet hours covering every minute of a longer stretch than this are treated as always quiet.
+const LONGEST_QUIET: i64 = 8 * 24 * 60;
+
+pub fn remind() -> Result<()> {
+    let mut app = App::load()?;
+    if let Some(end) = quiet_until(&app.config.quiet, LocalTime::now().naive_local())? {
+        if app.config.quiet.drop {
+            return Ok(());
+        }
+        let wait = end - LocalTime::now().naive_local();
+        thread::sleep(wait.to_std().unwrap_or_default());
+    }
+
+    let now = LocalTime::now().timestamp();
+    app.state.snoozed.retain(|_, until| *until > now);
+    // Looked up after any wait, so the digest is current when it is shown
+    let old: Vec<_> = stalled::old_tasks(&app)?
+        .into_iter()
+        .filter(|task| !app.state.snoozed.contains_key(&task.uuid().to_string()))
+        .collect();
+    app.state.save()?;
+    if old.is_empty() {
+        return Ok(());
+    }
+
+    if app.config.remind.per_task && per_task(&mut app, &old)? {
+        return Ok(());
+    }
+    digest(&mut app, &old)
+}
+
+fn digest(app: &mut App, old: &[Task]) -> Result<()> {
+    let summary = tr!(
+        "{} tasks untouched for {} days",
+        old.len(),
+        app.config.archive.days
+    );
+    let mut body: Vec<_> = old
+        .iter()
+        .take(DIGEST_LENGTH)
+        .map(|task| format!("• {}", task.description()))
+        .collect();
+    if old.len() > DIGEST_LENGTH {
+        body.push(tr!("and {} more", old.len() - DIGEST_LENGTH));
+    }
+    let body = body.join("\n");
+
+    // With an action notify-send waits and prints the action's name when it is clicked
+    let output = Command::new("notify-send")
+        .args(["--app-name", "taskwarrior-rofi"])
+        .arg(format!("--action=review={}", tr!("Review old tasks")))
+        .args([&summary, &body])
+        .output()
+        .context(tr!("running notify-send"))?;
+    if !output.status.success() {
+        // notify-send before libnotify 0.7.9 has no actions, so settle for the digest
+        Command::new("notify-send")
+            .args(["--app-name", "taskwarrior-rofi", &summary, &body])
+            .status()
+            .context(tr!("running notify-send"))?;
+        return Ok(());
+    }
+    if String::from_utf8_lossy(&output.stdout).trim() == "review" {
+        back_on_escape(stalled::review_old(app))?;
+    }
+    Ok(())
+}
+
+/// One notification per task, each with its own snooze actions. Returns false when notify-send
+/// has no actions, leaving the digest to be sent instead.
+fn per_task(app: &mut App, old: &[Task]) -> Result<bool> {
+    let summary = tr!("Untouched for {} days", app.config.archive.days);
+    let mut children = vec![];
+    for task in old.iter().take(DIGEST_LENGTH) {
+        let child = Command::new("notify-send")
+            .args(["--app-name", "taskwarrior-rofi"])
+            .arg(format!("--action=snooze-hour={}", tr!("Snooze 1h")))
+            .arg(format!("--action=snooze-day={}", tr!("Snooze 1d")))
+            .arg(format!("--action=review={}", tr!("Review old tasks")))
+            .args([&summary, task.description()])
+            .stdout(Stdio::piped())
+            .spawn()
+            .context(tr!("running notify-send"))?;
+        children.push((task, child));
+    }
+
+    // They wait together, so each can be answered in any order
+    let mut review = false;
+    for (task, child) in children {
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Ok(false);
+        }
+        let snooze = match String::from_utf8_lossy(&output.stdout).trim() {
+            "snooze-hour" => Duration::hours(1),
+            "snooze-day" => Duration::days(1),
+            "review" => {
+                review = true;
+                continue;
+            }
+            _ => continue,
+        };
+        let until = (LocalTime::now() + snooze).timestamp();
+        app.state.snoozed.insert(task.uuid().to_string(), until);
+    }
+    app.state.save()?;
+
+    if review {
+        back_on_escape(stalled::review_old(app))?;
+    }
+    Ok(true)
+}
+
+/// The end of the quiet hours `now` falls in, or `None` when it isn't quiet.
+fn quiet_until(config: &QuietConfig, now: NaiveDateTime) -> Result<Option<NaiveDateTime>> {
+    let is_quiet = |time| -> Result<bool> {
+        for window in &config.hours {
+            if window.matches(time, &tr!("quiet hours"))? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    };
+    if !is_quiet(now)? {
+        return Ok(None);
+    }
+    for minutes in 1..=LONGEST_QUIET {
+        let time = now + Duration::minutes(minutes);
+        if !is_quiet(time)? {
+            return Ok(Some(time));
+        }
+    }
+    bail!(tr!("The quiet hours never end"))
+}
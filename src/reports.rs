@@ -0,0 +1,98 @@
+//! The Report action: taskwarrior's reports that have a filter, and the `alias.*` entries in
+//! taskrc that lead to one, each opening its tasks for starting or finishing.
+//!
+//! An alias is followed through other aliases to the report it names, and whatever comes after
+//! the report's name is added to its filter, so `alias.work=next +work` lists `next`'s tasks
+//! tagged `work`. Aliases of commands that aren't reports with a filter, like
+//! `alias.burndown=burndown.daily`, can't be shown as a list and are left out.
+
+use crate::{
+    dates, query_sorted, quick, task_command,
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// How many aliases deep to look for a report, in case aliases point at each other.
+const MAX_ALIAS_DEPTH: usize = 8;
+
+struct Report {
+    name: String,
+    filter: String,
+}
+
+pub fn report(app: &App) -> Result<()> {
+    let (show, _) = task_command(vec!["_show"])?;
+    let settings: BTreeMap<_, _> = show.lines().filter_map(dates::split_setting).collect();
+
+    let mut rows = vec![];
+    for (key, filter) in &settings {
+        let name = match key
+            .strip_prefix("report.")
+            .and_then(|rest| rest.strip_suffix(".filter"))
+        {
+            Some(name) => name,
+            None => continue,
+        };
+        let label = match settings.get(format!("report.{}.description", name).as_str()) {
+            Some(description) => format!("{}\t{}", name, description),
+            None => name.to_string(),
+        };
+        rows.push(Row::new(
+            label,
+            Report {
+                name: name.to_string(),
+                filter: filter.to_string(),
+            },
+        ));
+    }
+    for key in settings.keys() {
+        let alias = match key.strip_prefix("alias.") {
+            Some(alias) => alias,
+            None => continue,
+        };
+        if let Some(filter) = resolve(&settings, alias) {
+            rows.push(Row::new(
+                tr!("{}\talias of {}", alias, settings[key]),
+                Report {
+                    name: alias.to_string(),
+                    filter,
+                },
+            ));
+        }
+    }
+    if rows.is_empty() {
+        bail!(tr!("No reports with a filter are defined"));
+    }
+
+    let report: Report = rich_rofi(&tr!("Report"), rows)?;
+    let tasks = query_sorted(&report.filter)?;
+    if tasks.is_empty() {
+        bail!(tr!("No tasks in {}", report.name));
+    }
+    quick::pick_and_act(&report.name, tasks, &app.config.style)
+}
+
+/// The filter of the report the alias ends at, with anything the aliases added.
+fn resolve(settings: &BTreeMap<&str, &str>, alias: &str) -> Option<String> {
+    let mut expansion = settings
+        .get(format!("alias.{}", alias).as_str())?
+        .to_string();
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let (first, rest) = match expansion.split_once(char::is_whitespace) {
+            Some((first, rest)) => (first.to_string(), rest.trim().to_string()),
+            None => (expansion.clone(), String::new()),
+        };
+        if let Some(filter) = settings.get(format!("report.{}.filter", first).as_str()) {
+            // Grouped so an `or` in the report's filter doesn't swallow what the alias adds
+            return Some(match rest.as_str() {
+                "" => filter.to_string(),
+                rest => format!("( {} ) {}", filter, rest),
+            });
+        }
+        let next = settings.get(format!("alias.{}", first).as_str())?;
+        expansion = format!("{} {}", next, rest).trim().to_string();
+    }
+    None
+}
@@ -0,0 +1,132 @@
+//! Bulk rescheduling: overdue tasks, the Monday-morning cleanup, and a project's tasks when its
+//! milestone slips.
+
+use crate::{
+    backup, date_picker, dates, format_task, query_sorted,
+    style::Style,
+    task_command,
+    widgets::{self, multi_select, rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{offset::Local as LocalTime, Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+enum Plan {
+    Today,
+    Tomorrow,
+    Spread,
+    Date,
+}
+
+pub fn reschedule_overdue(style: &Style) -> Result<()> {
+    let tasks = query_sorted("status:pending +OVERDUE")?;
+    if tasks.is_empty() {
+        bail!(tr!("Nothing is overdue"));
+    }
+
+    let items = tasks
+        .iter()
+        .map(|task| Row::new(format_task(task, style), task.uuid().to_string()))
+        .collect();
+    let uuids = multi_select(&tr!("Reschedule which?"), items)?;
+    if uuids.is_empty() {
+        return Ok(());
+    }
+
+    let plans = vec![
+        Row::new(tr!("Today"), Plan::Today),
+        Row::new(tr!("Tomorrow"), Plan::Tomorrow),
+        Row::new(tr!("Spread over the next N days"), Plan::Spread),
+        Row::new(tr!("Pick a date"), Plan::Date),
+    ];
+    let today = LocalTime::now().naive_local().date();
+    let mut by_date: BTreeMap<NaiveDate, Vec<&str>> = BTreeMap::new();
+    match rich_rofi(&tr!("New due date for {} tasks", uuids.len()), plans)? {
+        Plan::Today => {
+            by_date.insert(today, uuids.iter().map(String::as_str).collect());
+        }
+        Plan::Tomorrow => {
+            by_date.insert(
+                today + Duration::days(1),
+                uuids.iter().map(String::as_str).collect(),
+            );
+        }
+        Plan::Date => {
+            by_date.insert(
+                date_picker::pick(style)?,
+                uuids.iter().map(String::as_str).collect(),
+            );
+        }
+        Plan::Spread => {
+            let days: i64 =
+                widgets::validated(&tr!("Spread over how many days?"), &[], |input| match input
+                    .trim()
+                    .parse()
+                {
+                    Ok(days) if days >= 1 => Ok(days),
+                    Ok(_) => bail!(tr!("Spread over at least one day")),
+                    Err(_) => bail!(tr!("the number of days must be a whole number")),
+                })?;
+            for (idx, uuid) in uuids.iter().enumerate() {
+                let date = today + Duration::days(1 + idx as i64 % days);
+                by_date.entry(date).or_default().push(uuid);
+            }
+        }
+    }
+
+    backup::snapshot(
+        &uuids.iter().map(String::as_str).collect::<Vec<_>>(),
+        "reschedule",
+    )?;
+    // One command per date keeps the number of taskwarrior invocations small
+    for (date, uuids) in by_date {
+        let due = format!("due:{}", date.format("%Y-%m-%d"));
+        let mut args = vec!["rc.confirmation=off", "rc.bulk=0"];
+        args.extend(uuids);
+        args.extend(vec!["mod", &due]);
+        task_command(args).context(tr!("rescheduling tasks"))?;
+    }
+
+    Ok(())
+}
+
+/// Moves every pending task in a project that is due before a cutoff to one new due date.
+pub fn slip_project(app: &App) -> Result<()> {
+    let (projects, _) = task_command(vec!["_projects"])?;
+    let projects: Vec<_> = projects.lines().map(str::to_string).collect();
+    let project = widgets::text_with(&tr!("Slip which project?"), &projects)?;
+    let project = project.trim();
+    let cutoff = dates::pick_date(&tr!("Tasks due before?"), &dates::DUE_PRESETS, &app.config)?;
+    let tasks = query_sorted(&format!(
+        "status:pending project:{} due.before:{}",
+        project, cutoff
+    ))?;
+    if tasks.is_empty() {
+        bail!(tr!("Nothing in {} is due before {}", project, cutoff));
+    }
+
+    let target = dates::pick_date(&tr!("Move them to?"), &dates::DUE_PRESETS, &app.config)?;
+    let preview = tasks
+        .iter()
+        .map(|task| format_task(task, &app.config.style))
+        .collect();
+    if !widgets::confirm(
+        &tr!("Move {} tasks to {}?", tasks.len(), target),
+        &tr!("Move"),
+        &tr!("Cancel"),
+        preview,
+    )? {
+        return Ok(());
+    }
+
+    let uuids: Vec<_> = tasks.iter().map(|task| task.uuid().to_string()).collect();
+    let uuids: Vec<_> = uuids.iter().map(String::as_str).collect();
+    backup::snapshot(&uuids, "slip")?;
+    let due = format!("due:{}", target);
+    let mut args = vec!["rc.confirmation=off", "rc.bulk=0"];
+    args.extend(uuids);
+    args.extend(vec!["mod", &due]);
+    task_command(args).context(tr!("rescheduling tasks"))?;
+    Ok(())
+}
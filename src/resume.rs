@@ -0,0 +1,85 @@
+//! Resume: the last tasks that were stopped, for starting again with one key (Alt+1 for the most
+//! recent, Alt+2 for the one before and so on).
+//!
+//! Tasks stopped through the menu are remembered in the state. Stops made elsewhere are guessed
+//! at: pending tasks that aren't active but were modified in the last day (other than by being
+//! added), most recent first.
+
+use crate::{
+    conflict, export, format_task,
+    index::TaskIndex,
+    widgets::{pick_keyed, Row},
+    App,
+};
+use anyhow::{bail, Result};
+use chrono::{offset::Local as LocalTime, Duration, Utc};
+use task_hookrs::task::Task;
+
+/// One per custom key, `Alt+1` to `Alt+9`.
+const MAX_CANDIDATES: usize = 9;
+const RECENT_HOURS: i64 = 24;
+
+pub fn resume(app: &mut App) -> Result<()> {
+    let tasks = candidates(app)?;
+    if tasks.is_empty() {
+        bail!(tr!("Nothing was stopped recently"));
+    }
+
+    let keys: Vec<_> = (1..=tasks.len())
+        .map(|number| (number as u8, format!("Alt+{}", number)))
+        .collect();
+    let rows = tasks
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| {
+            Row::new(
+                format!("{}  {}", idx + 1, format_task(task, &app.config.style)),
+                idx,
+            )
+        })
+        .collect();
+    let (idx, key) = pick_keyed(&tr!("Resume"), rows, false, &keys)?;
+    let picked = &tasks[key.map_or(idx, |number| number as usize - 1)];
+
+    let mut task = picked.clone();
+    task.set_start(Some(LocalTime::now().naive_local()));
+    conflict::save(picked, &task, false)?;
+    Ok(())
+}
+
+/// Remembered stops first, then the guesses, leaving out anything active or no longer pending.
+fn candidates(app: &App) -> Result<Vec<Task>> {
+    let pending = TaskIndex::new(
+        export::query("status:pending")?
+            .into_iter()
+            .filter(|task| task.start().is_none())
+            .collect(),
+    );
+
+    let mut tasks = vec![];
+    let stopped = app.state.history("stopped");
+    for uuid in &stopped {
+        if let Some(task) = pending.get(uuid) {
+            tasks.push(task.clone());
+        }
+    }
+
+    // Taskwarrior exports dates in UTC
+    let cutoff = Utc::now().naive_utc() - Duration::hours(RECENT_HOURS);
+    let mut recent: Vec<_> = pending
+        .tasks()
+        .iter()
+        .filter(|task| !stopped.contains(&task.uuid().to_string()))
+        // A task modified only when it was added has never been worked on
+        .filter(|task| {
+            task.modified()
+                .is_some_and(|modified| **modified > cutoff && modified != task.entry())
+        })
+        .cloned()
+        .collect();
+    recent.sort_by_key(|task| std::cmp::Reverse(task.modified().map(|modified| **modified)));
+    tasks.extend(recent);
+
+    tasks.truncate(MAX_CANDIDATES);
+    Ok(tasks)
+}
@@ -0,0 +1,125 @@
+//! "Pick for me": a random task, weighted towards higher urgency.
+
+use crate::{
+    conflict, default_filter, query_sorted,
+    widgets::{self, rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Result};
+use chrono::offset::Local as LocalTime;
+use rand::{distributions::WeightedIndex, prelude::Distribution};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+/// Even a task with zero or negative urgency keeps a small chance of being picked.
+const MIN_WEIGHT: f64 = 0.1;
+
+enum Choice {
+    Start,
+    Done,
+    Skip,
+}
+
+pub fn roulette(app: &mut App) -> Result<()> {
+    // The default filter stays first, with earlier filters below it
+    let default = default_filter(app)?;
+    let mut filters = app.state.history("filter");
+    filters.retain(|filter| *filter != default);
+    filters.insert(0, default);
+    let filter = widgets::text_with(&tr!("Roulette filter"), &filters)?;
+    app.state.remember("filter", &filter)?;
+    let mut tasks = query_sorted(&filter)?;
+
+    loop {
+        let idx = match weighted_pick(&tasks, |task| task.urgency().unwrap_or(0.0)) {
+            Some(idx) => idx,
+            None => bail!(tr!("No tasks left to pick from")),
+        };
+
+        let choices = vec![
+            Row::new(tr!("Start"), Choice::Start),
+            Row::new(tr!("Done"), Choice::Done),
+            Row::new(tr!("Skip"), Choice::Skip),
+        ];
+        match rich_rofi(tasks[idx].description(), choices)? {
+            Choice::Start => {
+                let mut task = tasks[idx].clone();
+                task.set_start(Some(LocalTime::now().naive_local()));
+                conflict::save(&tasks[idx], &task, false)?;
+                return Ok(());
+            }
+            Choice::Done => {
+                let mut task = tasks[idx].clone();
+                *task.status_mut() = TaskStatus::Completed;
+                conflict::save(&tasks[idx], &task, false)?;
+                return Ok(());
+            }
+            Choice::Skip => {
+                tasks.swap_remove(idx);
+            }
+        }
+    }
+}
+
+/// Picks an index at random, with each item's chance proportional to its weight.
+fn weighted_pick<F>(tasks: &[Task], weight: F) -> Option<usize>
+where
+    F: Fn(&Task) -> f64,
+{
+    if tasks.is_empty() {
+        return None;
+    }
+    let weights = tasks.iter().map(|task| weight(task).max(MIN_WEIGHT));
+    let distribution = WeightedIndex::new(weights).ok()?;
+    Some(distribution.sample(&mut rand::thread_rng()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(description: &str) -> Task {
+        serde_json::from_value(json!({
+            "uuid": "8ca953d5-18b6-4eb1-a2b3-f38f1b6d8b9a",
+            "description": description,
+            "status": "pending",
+            "entry": "20261014T090000Z",
+        }))
+        .unwrap()
+    }
+
+    fn weight(task: &Task) -> f64 {
+        task.description().parse().unwrap()
+    }
+
+    fn picks(tasks: &[Task]) -> Vec<usize> {
+        let mut counts = vec![0; tasks.len()];
+        for _ in 0..1000 {
+            counts[weighted_pick(tasks, weight).unwrap()] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn nothing_to_pick() {
+        assert_eq!(weighted_pick(&[], weight), None);
+    }
+
+    #[test]
+    fn only_one_to_pick() {
+        assert_eq!(weighted_pick(&[task("-3")], weight), Some(0));
+    }
+
+    #[test]
+    fn heavier_tasks_come_up_more() {
+        let counts = picks(&[task("1"), task("100")]);
+        assert!(counts[1] > counts[0] * 10, "{:?}", counts);
+    }
+
+    #[test]
+    fn low_urgency_still_comes_up() {
+        // Both fall back to the minimum weight, so they're even
+        let counts = picks(&[task("0"), task("-8")]);
+        assert!(counts.iter().all(|count| *count > 300), "{:?}", counts);
+    }
+}
@@ -0,0 +1,87 @@
+//! Time-of-day rules choosing a profile at startup, e.g. a work profile on weekdays 9–17 and a
+//! home profile otherwise. The first profile whose days and hours match the current time wins.
+//!
+//! ```toml
+//! [[profiles]]
+//! name = "work"
+//! days = ["mon", "tue", "wed", "thu", "fri"]
+//! from = "09:00"
+//! until = "17:00"
+//! filter = "+work status:pending"
+//! action = "Start"
+//! ```
+//!
+//! `days` defaults to every day and the hours to the whole day; `until` before `from` spans
+//! midnight. `filter` replaces the default report's filter in task pickers and `action` is moved
+//! to the top of the main menu.
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    #[serde(flatten)]
+    window: Window,
+    pub filter: Option<String>,
+    pub action: Option<String>,
+}
+
+/// Days of the week and a time of day range, shared by profiles and quiet hours.
+#[derive(Deserialize, Clone)]
+pub struct Window {
+    #[serde(default)]
+    days: Vec<String>,
+    from: Option<String>,
+    until: Option<String>,
+}
+
+impl Window {
+    /// `label` names the window in errors, like `profile work`.
+    pub fn matches(&self, now: NaiveDateTime, label: &str) -> Result<bool> {
+        if !self.days.is_empty() {
+            let mut today = false;
+            for day in &self.days {
+                let day: Weekday = day
+                    .parse()
+                    .map_err(|_| anyhow!(tr!("{}: unknown day `{}`", label, day)))?;
+                today |= day == now.weekday();
+            }
+            if !today {
+                return Ok(false);
+            }
+        }
+
+        let from = time(&self.from, label)?.unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0));
+        let until = time(&self.until, label)?;
+        let time = now.time();
+        Ok(match until {
+            None => time >= from,
+            Some(until) if from <= until => time >= from && time < until,
+            Some(until) => time >= from || time < until,
+        })
+    }
+}
+
+fn time(value: &Option<String>, label: &str) -> Result<Option<NaiveTime>> {
+    value
+        .as_ref()
+        .map(|value| {
+            NaiveTime::parse_from_str(value, "%H:%M")
+                .map_err(|_| anyhow!(tr!("{}: `{}` is not an HH:MM time", label, value)))
+        })
+        .transpose()
+}
+
+pub fn active_profile(profiles: &[Profile], now: NaiveDateTime) -> Result<Option<Profile>> {
+    for profile in profiles {
+        if profile
+            .window
+            .matches(now, &tr!("profile {}", profile.name))?
+        {
+            return Ok(Some(profile.clone()));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,192 @@
+//! Rhai scripting hooks.
+//!
+//! Every `*.rhai` file in `~/.config/taskwarrior-rofi/scripts/` is compiled at startup. A script
+//! can define any of these functions, each taking the task as an object map shaped like
+//! `task export` output:
+//!
+//! - `format(task)` returns the row label used in task pickers. The first script defining it wins.
+//! - `filter(task)` returns `false` to hide a task from pickers. Every defining script must agree.
+//! - `action(task)` lists the script as an action named after its file, run on a chosen task.
+//!
+//! Scripts run without file or process access. They get these primitives instead:
+//!
+//! - `query(filter)` returns an array of task maps matching a taskwarrior filter.
+//! - `mutate(uuid, mods)` runs `task <uuid> mod <mods>`. It takes one task's UUID, not a filter,
+//!   and no `rc.` overrides.
+//! - `prompt(text)` asks for free-form input in rofi and returns it.
+//!
+//! Builds without the `scripting` feature load no scripts and have no script actions.
+
+use anyhow::Result;
+use task_hookrs::task::Task;
+#[cfg(feature = "scripting")]
+use {
+    crate::{export, is_uuid, paths, task_command, widgets},
+    anyhow::{anyhow, Context},
+    rhai::{Dynamic, Engine, EvalAltResult, Scope, AST},
+    std::fs,
+};
+
+#[cfg(feature = "scripting")]
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+#[cfg(feature = "scripting")]
+struct Script {
+    name: String,
+    ast: AST,
+}
+
+#[cfg(feature = "scripting")]
+impl Script {
+    fn defines(&self, function: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == function && f.params.len() == 1)
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl Scripts {
+    pub fn load() -> Result<Self> {
+        let engine = sandboxed_engine();
+        let mut scripts = vec![];
+
+        let dir = paths::scripts_dir()?;
+        if dir.is_dir() {
+            for entry in fs::read_dir(&dir).with_context(|| tr!("reading {}", dir.display()))? {
+                let path = entry?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("rhai") {
+                    continue;
+                }
+                let name = path
+                    .file_stem()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let source = fs::read_to_string(&path)?;
+                let ast = engine
+                    .compile(&source)
+                    .map_err(|err| anyhow!(tr!("compiling script {}: {}", name, err)))?;
+                scripts.push(Script { name, ast });
+            }
+        }
+        scripts.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { engine, scripts })
+    }
+
+    pub fn actions(&self) -> Vec<String> {
+        self.scripts
+            .iter()
+            .filter(|script| script.defines("action"))
+            .map(|script| script.name.clone())
+            .collect()
+    }
+
+    pub fn format(&self, task: &Task) -> Result<Option<String>> {
+        match self.scripts.iter().find(|script| script.defines("format")) {
+            Some(script) => Ok(Some(self.call(script, "format", task)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn filter(&self, task: &Task) -> Result<bool> {
+        for script in self
+            .scripts
+            .iter()
+            .filter(|script| script.defines("filter"))
+        {
+            if !self.call::<bool>(script, "filter", task)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn run_action(&self, name: &str, task: &Task) -> Result<()> {
+        let script = self
+            .scripts
+            .iter()
+            .find(|script| script.name == name)
+            .ok_or_else(|| anyhow!(tr!("No script named {}", name)))?;
+        self.call::<Dynamic>(script, "action", task).map(|_| ())
+    }
+
+    fn call<T: Clone + 'static>(&self, script: &Script, function: &str, task: &Task) -> Result<T> {
+        let task = rhai::serde::to_dynamic(task)
+            .map_err(|err| anyhow!(tr!("converting task for script: {}", err)))?;
+        self.engine
+            .call_fn(&mut Scope::new(), &script.ast, function, (task,))
+            .map_err(|err| anyhow!(tr!("script {}: {}", script.name, err)))
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(100_000);
+
+    engine.register_fn(
+        "query",
+        |filter: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            let tasks = export::query(filter).map_err(|err| err.to_string())?;
+            rhai::serde::to_dynamic(&tasks)
+        },
+    );
+    engine.register_fn(
+        "mutate",
+        |uuid: &str, mods: &str| -> Result<(), Box<EvalAltResult>> {
+            // A filter in place of the UUID could change any number of tasks
+            if !is_uuid(uuid) {
+                return Err(format!("mutate: `{}` is not a task UUID", uuid).into());
+            }
+            if mods.split_whitespace().any(|word| word.starts_with("rc.")) {
+                return Err("mutate: rc. overrides aren't allowed".into());
+            }
+            let mut args = vec![uuid, "mod"];
+            args.extend(mods.split_whitespace());
+            task_command(args).map_err(|err| err.to_string())?;
+            Ok(())
+        },
+    );
+    engine.register_fn(
+        "prompt",
+        |text: &str| -> Result<String, Box<EvalAltResult>> {
+            Ok(widgets::text(text).map_err(|err| err.to_string())?)
+        },
+    );
+
+    engine
+}
+
+#[cfg(not(feature = "scripting"))]
+pub struct Scripts;
+
+#[cfg(not(feature = "scripting"))]
+impl Scripts {
+    pub fn load() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub fn actions(&self) -> Vec<String> {
+        vec![]
+    }
+
+    pub fn format(&self, _task: &Task) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    pub fn filter(&self, _task: &Task) -> Result<bool> {
+        Ok(true)
+    }
+
+    pub fn run_action(&self, name: &str, _task: &Task) -> Result<()> {
+        anyhow::bail!(tr!("No script named {}", name))
+    }
+}
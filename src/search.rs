@@ -0,0 +1,154 @@
+//! Searching pending tasks from GNOME Shell's overview: `org.gnome.Shell.SearchProvider2`, served
+//! by the `--watch` D-Bus service (see `dbus`) at `/org/taskwarrior_rofi/SearchProvider`. It gives
+//! a result set for some terms, a name and description per result, and activating a result opens
+//! its Start/Done menu.
+//!
+//! Results are identified by UUID. A task matches when every term appears, ignoring case, in its
+//! description, project or tags.
+//!
+//! GNOME Shell finds providers by a file in `/usr/share/gnome-shell/search-providers/`, say
+//! `taskwarrior-rofi.search-provider.ini`, next to a `taskwarrior-rofi.desktop` that the overview
+//! settings list it under:
+//!
+//! ```ini
+//! [Shell Search Provider]
+//! DesktopId=taskwarrior-rofi.desktop
+//! BusName=org.taskwarrior_rofi
+//! ObjectPath=/org/taskwarrior_rofi/SearchProvider
+//! Version=2
+//! ```
+
+use crate::{
+    config::Config, dbus::failed, export, format_task, index::TaskIndex, is_uuid, query_sorted,
+    quick, style::Style, watch::Wake,
+};
+use anyhow::{anyhow, Result};
+use std::{collections::HashMap, sync::mpsc::Sender};
+use task_hookrs::task::Task;
+use zbus::{
+    fdo, interface,
+    zvariant::{OwnedValue, Str},
+};
+
+pub const PATH: &str = "/org/taskwarrior_rofi/SearchProvider";
+
+pub struct SearchProvider {
+    pub wakes: Sender<Wake>,
+}
+
+#[interface(name = "org.gnome.Shell.SearchProvider2")]
+impl SearchProvider {
+    fn get_initial_result_set(&self, terms: Vec<String>) -> fdo::Result<Vec<String>> {
+        let terms: Vec<_> = terms.iter().map(String::as_str).collect();
+        results(&terms).map_err(failed)
+    }
+
+    /// The shell calls this as more is typed, so the results only narrow.
+    fn get_subsearch_result_set(
+        &self,
+        previous_results: Vec<String>,
+        terms: Vec<String>,
+    ) -> fdo::Result<Vec<String>> {
+        let terms: Vec<_> = terms.iter().map(String::as_str).collect();
+        Ok(results(&terms)
+            .map_err(failed)?
+            .into_iter()
+            .filter(|id| previous_results.contains(id))
+            .collect())
+    }
+
+    fn get_result_metas(
+        &self,
+        identifiers: Vec<String>,
+    ) -> fdo::Result<Vec<HashMap<String, OwnedValue>>> {
+        let ids: Vec<_> = identifiers.iter().map(String::as_str).collect();
+        let style = Config::load().map_err(failed)?.style;
+        let string = |value: String| OwnedValue::from(Str::from(value));
+        Ok(result_metas(&ids, &style)
+            .map_err(failed)?
+            .into_iter()
+            .map(|meta| {
+                HashMap::from([
+                    ("id".to_string(), string(meta.id)),
+                    ("name".to_string(), string(meta.name)),
+                    ("description".to_string(), string(meta.description)),
+                ])
+            })
+            .collect())
+    }
+
+    // The arguments are named for the introspection data, though only the identifier matters
+    #[allow(unused_variables)]
+    fn activate_result(&self, identifier: &str, terms: Vec<String>, timestamp: u32) {
+        // Identifiers come from the shell, and anything but a UUID would be a filter
+        if is_uuid(identifier) {
+            let _ = self.wakes.send(Wake::Task(identifier.to_string()));
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn launch_search(&self, terms: Vec<String>, timestamp: u32) {
+        let _ = self.wakes.send(Wake::Menu);
+    }
+}
+
+pub struct ResultMeta {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// `GetInitialResultSet`: pending tasks matching all of `terms`.
+pub fn results(terms: &[&str]) -> Result<Vec<String>> {
+    let terms: Vec<_> = terms.iter().map(|term| term.to_lowercase()).collect();
+    Ok(query_sorted("status:pending")?
+        .iter()
+        .filter(|task| {
+            let text = searchable(task);
+            terms.iter().all(|term| text.contains(term))
+        })
+        .map(|task| task.uuid().to_string())
+        .collect())
+}
+
+/// `GetResultMetas`: what the overview shows for each result.
+pub fn result_metas(ids: &[&str], style: &Style) -> Result<Vec<ResultMeta>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    // One export for all of them rather than one per result
+    let tasks = TaskIndex::new(export::query(&ids.join(" "))?);
+    let mut metas = vec![];
+    for id in ids {
+        if let Some(task) = tasks.get(id) {
+            metas.push(ResultMeta {
+                id: id.to_string(),
+                name: task.description().to_string(),
+                description: format_task(task, style),
+            });
+        }
+    }
+    Ok(metas)
+}
+
+/// `ActivateResult`: the task's Start/Done menu.
+pub fn activate(id: &str, style: &Style) -> Result<()> {
+    let task = export::query(id)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!(tr!("{} no longer exists", id)))?;
+    quick::act(&task, style)
+}
+
+fn searchable(task: &Task) -> String {
+    let mut text = task.description().to_lowercase();
+    if let Some(project) = task.project() {
+        text.push(' ');
+        text.push_str(&project.to_lowercase());
+    }
+    for tag in task.tags().into_iter().flatten() {
+        text.push(' ');
+        text.push_str(&tag.to_lowercase());
+    }
+    text
+}
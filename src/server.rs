@@ -0,0 +1,242 @@
+//! `taskwarrior-rofi serve`, with the `http-api` feature: a small HTTP server so browser
+//! extensions or a phone on the LAN can use the same logic as the menu.
+//!
+//! ```toml
+//! [server]
+//! listen = "127.0.0.1:8765"
+//! token = "long random string"
+//! ```
+//!
+//! Every request needs `Authorization: Bearer <token>`, and the server won't start without one.
+//! Bodies and answers are JSON.
+//!
+//! - `GET /tasks?filter=...` exports matching tasks, by default the default report's
+//! - `GET /filters` lists the default filter and earlier Roulette filters
+//! - `GET /stats` counts pending, active, overdue and completed tasks
+//! - `POST /tasks` with `{"text": "...", "annotations": [...]}` adds a task, answering with its `uuid`
+//! - `POST /tasks/<uuid>/done` completes a task
+//! - `POST /tasks/<uuid>/annotate` with `{"text": "..."}` annotates one
+//!
+//! The mutating endpoints answer 403 in read-only mode.
+
+use crate::{add_task_quietly, default_filter, is_uuid, mutation, query_sorted, task_command, App};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+/// Bodies bigger than this are refused rather than read.
+const MAX_BODY: usize = 64 * 1024;
+
+/// Connections are handled one at a time, so a client that stops talking is dropped after this.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct NewTask {
+    text: String,
+    #[serde(default)]
+    annotations: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct NewAnnotation {
+    text: String,
+}
+
+pub fn serve() -> Result<()> {
+    let app = App::load()?;
+    let config = &app.config.server;
+    let token = config
+        .token
+        .as_deref()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| anyhow!(tr!("Set [server] token before serving")))?;
+    let listener =
+        TcpListener::bind(&config.listen).with_context(|| tr!("listening on {}", config.listen))?;
+    println!("{}", tr!("Listening on http://{}", config.listen));
+
+    for stream in listener.incoming().flatten() {
+        // One bad connection shouldn't stop the server
+        let _ = handle(stream, &app, token);
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, app: &App, token: &str) -> Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let (status, body) = match read_request(&stream) {
+        Ok(request) if request.token.as_deref() != Some(token) => {
+            (401, json!({ "error": tr!("missing or wrong token") }))
+        }
+        Ok(request) => match route(&request, app) {
+            Ok((status, body)) => (status, body),
+            Err(err) => (500, json!({ "error": err.to_string() })),
+        },
+        Err(err) => (400, json!({ "error": err.to_string() })),
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn route(request: &Request, app: &App) -> Result<(u16, Value)> {
+    let segments: Vec<_> = request.path.trim_matches('/').split('/').collect();
+    let mutating = request.method == "POST";
+    if mutating && app.config.read_only {
+        return Ok((403, json!({ "error": tr!("This menu is read-only") })));
+    }
+
+    Ok(match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["tasks"]) => {
+            let filter = match request.query.iter().find(|(key, _)| key == "filter") {
+                Some((_, filter)) => filter.clone(),
+                None => default_filter(app)?,
+            };
+            (200, json!(query_sorted(&filter)?))
+        }
+        ("GET", ["filters"]) => {
+            let mut filters = vec![default_filter(app)?];
+            filters.extend(app.state.history("filter"));
+            filters.dedup();
+            (200, json!(filters))
+        }
+        ("GET", ["stats"]) => {
+            let count = |filter: &str| -> Result<usize> { Ok(query_sorted(filter)?.len()) };
+            (
+                200,
+                json!({
+                    "pending": count("status:pending")?,
+                    "active": count("status:pending +ACTIVE")?,
+                    "overdue": count("status:pending +OVERDUE")?,
+                    "completed": count("status:completed")?,
+                }),
+            )
+        }
+        ("POST", ["tasks"]) => {
+            let new: NewTask = serde_json::from_slice(&request.body)?;
+            let uuid = add_task_quietly(&new.text, &new.annotations)?;
+            (201, json!({ "uuid": uuid }))
+        }
+        // Anything else there would be a filter, which could match every task
+        ("POST", ["tasks", uuid, "done"]) if is_uuid(uuid) => {
+            task_command(vec![uuid, "done"]).context(tr!("completing task"))?;
+            (200, json!({}))
+        }
+        ("POST", ["tasks", uuid, "annotate"]) if is_uuid(uuid) => {
+            let new: NewAnnotation = serde_json::from_slice(&request.body)?;
+            mutation::annotate(uuid, &new.text)?;
+            (200, json!({}))
+        }
+        _ => (404, json!({ "error": tr!("no such endpoint") })),
+    })
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => bail!(tr!("not an HTTP request")),
+    };
+
+    let mut token = None;
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = match header.split_once(':') {
+            Some((name, value)) => (name.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        match name.as_str() {
+            "authorization" => token = value.strip_prefix("Bearer ").map(str::to_string),
+            "content-length" => length = value.parse()?,
+            _ => {}
+        }
+    }
+    if length > MAX_BODY {
+        bail!(tr!("request body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+    Ok(Request {
+        method,
+        path: decode(path),
+        query,
+        token,
+        body,
+    })
+}
+
+/// Undoes percent-encoding, with `+` as a space as in form-encoded queries.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = vec![];
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[idx], escaped) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                idx += 3;
+                continue;
+            }
+            (b'+', _) => out.push(b' '),
+            (byte, _) => out.push(byte),
+        }
+        idx += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,66 @@
+//! A tally of what was done since the menu opened, shown on exit with `[session] summary = true`.
+//!
+//! Changes are counted where they are made: `task` commands that add or complete, and whole-task
+//! saves. Tracked time is the time between start and stop of tasks stopped through the menu.
+
+use crate::{command_word, duration, is_uuid};
+use chrono::{Duration, Utc};
+use std::sync::Mutex;
+use task_hookrs::{status::TaskStatus, task::Task};
+
+struct Tally {
+    completed: usize,
+    added: usize,
+    /// Seconds.
+    tracked: i64,
+}
+
+static TALLY: Mutex<Tally> = Mutex::new(Tally {
+    completed: 0,
+    added: 0,
+    tracked: 0,
+});
+
+/// Counts a `task` command that succeeded.
+pub fn command(args: &[&str]) {
+    let mut tally = TALLY.lock().expect("tally lock");
+    match command_word(args) {
+        // One done can finish several tasks named by UUID
+        Some("done") => tally.completed += args.iter().filter(|arg| is_uuid(arg)).count().max(1),
+        Some("add" | "log") => tally.added += 1,
+        _ => {}
+    }
+}
+
+/// Counts a task saved whole, `before` being the copy it was changed from.
+pub fn saved(before: &Task, after: &Task) {
+    let mut tally = TALLY.lock().expect("tally lock");
+    if *after.status() == TaskStatus::Completed && *before.status() != TaskStatus::Completed {
+        tally.completed += 1;
+    }
+    if let (Some(start), None) = (before.start(), after.start()) {
+        // Taskwarrior exports dates in UTC
+        tally.tracked += (Utc::now().naive_utc() - **start).num_seconds();
+    }
+}
+
+/// Like "3 completed, 1 added, 25m tracked", or `None` when nothing happened.
+pub fn summary() -> Option<String> {
+    let tally = TALLY.lock().expect("tally lock");
+    let mut parts = vec![];
+    if tally.completed > 0 {
+        parts.push(tr!("{} completed", tally.completed));
+    }
+    if tally.added > 0 {
+        parts.push(tr!("{} added", tally.added));
+    }
+    if tally.tracked >= 60 {
+        let tracked = duration::format(Duration::minutes(tally.tracked / 60));
+        parts.push(tr!("{} tracked", tracked));
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(", "))
+    }
+}
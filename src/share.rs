@@ -0,0 +1,129 @@
+//! Sharing a task as a message built from a template, copied or handed to a command.
+//!
+//! ```toml
+//! [share]
+//! template = "{description}\nDue: {due}\n{links}"
+//!
+//! [[share.targets]]
+//! name = "Email"
+//! command = "xdg-email --subject {description} --body {message}"
+//!
+//! [[share.targets]]
+//! name = "Slack"
+//! command = "curl -s -X POST --data-urlencode text@- https://hooks.slack.com/services/..."
+//! ```
+//!
+//! The placeholders are `{description}`, `{project}`, `{due}`, `{tags}`, `{uuid}` and `{links}`
+//! (one link annotation per line). Template lines whose placeholders are all empty are left out,
+//! so the `Due:` line disappears for tasks without a due date. A target command is split on
+//! whitespace with `{message}` and the other placeholders substituted in each argument; one
+//! without `{message}` gets the message on stdin instead.
+
+use crate::{
+    config::ShareConfig,
+    copy::to_clipboard,
+    privacy,
+    widgets::{rich_rofi, Row},
+    TaskExt,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{offset::Local as LocalTime, TimeZone};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+use task_hookrs::task::Task;
+
+pub fn share(task: &Task, config: &ShareConfig) -> Result<()> {
+    let fields = fields(&privacy::masked(task));
+    let message = render(&config.template, &fields);
+
+    let mut choices = vec![Row::new(tr!("Copy message"), None)];
+    choices.extend(
+        config
+            .targets
+            .iter()
+            .map(|target| Row::new(target.name.clone(), Some(target))),
+    );
+    match rich_rofi(&tr!("Share"), choices)? {
+        None => to_clipboard(&message),
+        Some(target) => send(&target.command, &message, &fields),
+    }
+}
+
+fn fields(task: &Task) -> Vec<(&'static str, String)> {
+    let due = task
+        .due()
+        .map(|due| {
+            LocalTime
+                .from_utc_datetime(due)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let links: Vec<_> = task
+        .link_annotations()
+        .into_iter()
+        .map(|ann| ann.description().as_str())
+        .collect();
+    vec![
+        ("{description}", task.description().to_string()),
+        ("{project}", task.project().cloned().unwrap_or_default()),
+        ("{due}", due),
+        (
+            "{tags}",
+            task.tags().map(|tags| tags.join(" ")).unwrap_or_default(),
+        ),
+        ("{uuid}", task.uuid().to_string()),
+        ("{links}", links.join("\n")),
+    ]
+}
+
+/// Fills in the template line by line, dropping lines that had placeholders but none with a value.
+fn render(template: &str, fields: &[(&str, String)]) -> String {
+    let mut lines = vec![];
+    for line in template.lines() {
+        let used: Vec<_> = fields
+            .iter()
+            .filter(|(placeholder, _)| line.contains(placeholder))
+            .collect();
+        if !used.is_empty() && used.iter().all(|(_, value)| value.is_empty()) {
+            continue;
+        }
+        lines.push(substitute(line, fields));
+    }
+    lines.join("\n")
+}
+
+fn substitute(text: &str, fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .fold(text.to_string(), |text, (placeholder, value)| {
+            text.replace(placeholder, value)
+        })
+}
+
+fn send(command: &str, message: &str, fields: &[(&str, String)]) -> Result<()> {
+    let pipe = !command.contains("{message}");
+    let args: Vec<String> = command
+        .split_whitespace()
+        .map(|arg| substitute(&arg.replace("{message}", message), fields))
+        .collect();
+    if args.is_empty() {
+        bail!(tr!("The configured command is empty"));
+    }
+
+    let mut child = Command::new(&args[0])
+        .args(&args[1..])
+        .stdin(if pipe { Stdio::piped() } else { Stdio::null() })
+        .spawn()
+        .with_context(|| tr!("running {}", args[0]))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!(tr!("`{}` failed with {}", args[0], status));
+    }
+    Ok(())
+}
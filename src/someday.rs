@@ -0,0 +1,115 @@
+//! Someday/maybe: tasks parked with `+someday` and `wait:someday`, so they stay out of every
+//! report until brought back. Every `[someday] review_days` (default 7, 0 turns it off) the
+//! Stalled review and `review-old` first ask whether to go through the list, so old ideas
+//! resurface instead of rotting.
+
+use crate::{
+    back_on_escape, conflict, query_sorted, task_command,
+    widgets::{self, rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{offset::Local as LocalTime, Duration, NaiveDate, Utc};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+const FILTER: &str = "+someday -COMPLETED -DELETED";
+
+enum Choice {
+    Activate,
+    Delete,
+    Keep,
+}
+
+pub fn park(task: &Task) -> Result<()> {
+    task_command(vec![
+        &task.uuid().to_string(),
+        "mod",
+        "+someday",
+        "wait:someday",
+    ])
+    .context(tr!("moving task to someday"))?;
+    Ok(())
+}
+
+/// The someday list, oldest first, with ways to bring tasks back or drop them unless
+/// `read_only`. Keeps coming back to the list until escaped or emptied.
+pub fn browse(read_only: bool) -> Result<()> {
+    if query_sorted(FILTER)?.is_empty() {
+        bail!(tr!("The someday list is empty"));
+    }
+    loop {
+        let mut tasks = query_sorted(FILTER)?;
+        if tasks.is_empty() {
+            return Ok(());
+        }
+        tasks.sort_by_key(|task| **task.entry());
+        decide(tasks, read_only)?;
+    }
+}
+
+fn decide(tasks: Vec<Task>, read_only: bool) -> Result<()> {
+    let rows = tasks
+        .into_iter()
+        .map(|task| {
+            // Taskwarrior exports dates in UTC
+            let age = (Utc::now().naive_utc() - **task.entry()).num_days();
+            Row::new(
+                format!("{} ({})", task.description(), tr!("{}d ago", age)),
+                task,
+            )
+        })
+        .collect();
+
+    let picked: Task = rich_rofi(&tr!("Someday"), rows)?;
+    if read_only {
+        return Ok(());
+    }
+    let choices = vec![
+        Row::new(tr!("Do it now"), Choice::Activate),
+        Row::new(tr!("Keep for someday"), Choice::Keep),
+        Row::new(tr!("Delete"), Choice::Delete),
+    ];
+    match rich_rofi(picked.description(), choices)? {
+        Choice::Activate => {
+            task_command(vec![&picked.uuid().to_string(), "mod", "-someday", "wait:"])
+                .context(tr!("bringing task back from someday"))?;
+        }
+        Choice::Delete => {
+            let mut deleted = picked.clone();
+            *deleted.status_mut() = TaskStatus::Deleted;
+            conflict::save(&picked, &deleted, false)?;
+        }
+        Choice::Keep => {}
+    }
+    Ok(())
+}
+
+/// Offers a pass through the someday list when the last one was long enough ago.
+pub fn review_if_due(app: &mut App) -> Result<()> {
+    let days = app.config.someday.review_days;
+    if days <= 0 || app.config.read_only {
+        return Ok(());
+    }
+    let today = LocalTime::now().naive_local().date();
+    let last = app
+        .state
+        .someday_reviewed
+        .as_deref()
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+    if last.is_some_and(|last| today - last < Duration::days(days)) {
+        return Ok(());
+    }
+
+    let count = query_sorted(FILTER)?.len();
+    // Asked at most once per interval, whatever the answer
+    app.state.someday_reviewed = Some(today.format("%Y-%m-%d").to_string());
+    app.state.save()?;
+    if count == 0 {
+        return Ok(());
+    }
+    let prompt = tr!("{} tasks on the someday list. Review them?", count);
+    if widgets::confirm(&prompt, &tr!("Review"), &tr!("Not now"), vec![])? {
+        back_on_escape(browse(false))?;
+    }
+    Ok(())
+}
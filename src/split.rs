@@ -0,0 +1,79 @@
+//! Splitting a task that turned out to be several: the new tasks copy its project, tags and due
+//! date, each annotation moves to one of them or stays behind, and the original is completed or
+//! deleted.
+
+use crate::{
+    conflict, create_task, mutation, privacy,
+    widgets::{self, rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+enum Ending {
+    Complete,
+    Delete,
+    Keep,
+}
+
+pub fn split(task: &Task) -> Result<()> {
+    let mut descriptions = vec![];
+    loop {
+        let prompt = tr!("Part {} of {}", descriptions.len() + 1, task.description());
+        match widgets::text(&prompt) {
+            Ok(description) if !description.trim().is_empty() => {
+                descriptions.push(description.trim().to_string())
+            }
+            Ok(_) | Err(rofi::Error::Blank) | Err(rofi::Error::Interrupted) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if descriptions.len() < 2 {
+        bail!(tr!("A split needs at least two parts"));
+    }
+
+    let mut inherited = vec![];
+    if let Some(project) = task.project() {
+        inherited.push(format!("project:{}", project));
+    }
+    if let Some(tags) = task.tags() {
+        inherited.extend(tags.iter().map(|tag| format!("+{}", tag)));
+    }
+    if let Some(due) = task.due() {
+        // Taskwarrior exports dates in UTC
+        inherited.push(format!("due:{}", due.format("%Y-%m-%dT%H:%M:%SZ")));
+    }
+
+    let mut parts = vec![];
+    for description in &descriptions {
+        let mut args = vec!["add"];
+        args.extend(description.split_whitespace());
+        args.extend(inherited.iter().map(String::as_str));
+        parts.push(create_task(args).context(tr!("adding part"))?);
+    }
+
+    for annotation in task.annotations().into_iter().flatten() {
+        let mut rows: Vec<_> = descriptions
+            .iter()
+            .zip(&parts)
+            .map(|(description, id)| Row::new(tr!("Move to {}", description), Some(id)))
+            .collect();
+        rows.push(Row::new(tr!("Leave it"), None));
+        if let Some(id) = rich_rofi(&privacy::shown(annotation), rows)? {
+            mutation::annotate(id, annotation.description())?;
+        }
+    }
+
+    let endings = vec![
+        Row::new(tr!("Complete the original"), Ending::Complete),
+        Row::new(tr!("Delete the original"), Ending::Delete),
+        Row::new(tr!("Keep the original"), Ending::Keep),
+    ];
+    let mut original = task.clone();
+    match rich_rofi(task.description(), endings)? {
+        Ending::Complete => *original.status_mut() = TaskStatus::Completed,
+        Ending::Delete => *original.status_mut() = TaskStatus::Deleted,
+        Ending::Keep => return Ok(()),
+    }
+    conflict::save(task, &original, false)?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Zombie tasks: urgent ones nobody has touched in a while, with ways to deal with them.
+//!
+//! ```toml
+//! [stalled]
+//! days = 14          # untouched for at least this long
+//! min_urgency = 5.0
+//!
+//! [archive]
+//! days = 90          # old tasks, whatever their urgency
+//! ```
+//!
+//! Old tasks are what `remind` sends a digest about, and what `review-old` opens. Both reviews
+//! start by offering the someday list when its review is due (see `someday`).
+
+use crate::{
+    conflict, dates, format_task, query_sorted, someday, subtask, task_command,
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use task_hookrs::{date::Date, status::TaskStatus, task::Task};
+
+enum Choice {
+    Postpone,
+    BreakDown,
+    Delete,
+    Back,
+}
+
+pub fn stalled(app: &mut App) -> Result<()> {
+    someday::review_if_due(app)?;
+    let config = &app.config.stalled;
+    let tasks: Vec<_> = untouched_for(config.days)?
+        .into_iter()
+        .filter(|task| task.urgency().unwrap_or(0.0) >= config.min_urgency)
+        .collect();
+    if tasks.is_empty() {
+        bail!(tr!("Nothing has stalled for {} days", config.days));
+    }
+    review(app, &tr!("Stalled"), tasks)
+}
+
+/// Pending tasks with no activity for `[archive] days`.
+pub fn old_tasks(app: &App) -> Result<Vec<Task>> {
+    untouched_for(app.config.archive.days)
+}
+
+pub fn review_old(app: &mut App) -> Result<()> {
+    someday::review_if_due(app)?;
+    let tasks = old_tasks(app)?;
+    if tasks.is_empty() {
+        bail!(tr!(
+            "No tasks untouched for {} days",
+            app.config.archive.days
+        ));
+    }
+    review(app, &tr!("Old tasks"), tasks)
+}
+
+fn untouched_for(days: i64) -> Result<Vec<Task>> {
+    // Taskwarrior exports dates in UTC
+    let cutoff = Utc::now().naive_utc() - Duration::days(days);
+    Ok(query_sorted("status:pending")?
+        .into_iter()
+        .filter(|task| **last_touched(task) < cutoff)
+        .collect())
+}
+
+fn review(app: &App, prompt: &str, tasks: Vec<Task>) -> Result<()> {
+    let rows: Vec<_> = tasks
+        .into_iter()
+        .map(|task| {
+            let idle = (Utc::now().naive_utc() - **last_touched(&task)).num_days();
+            Row::new(
+                format!("{} ({}d)", format_task(&task, &app.config.style), idle),
+                task,
+            )
+        })
+        .collect();
+
+    let task: Task = rich_rofi(prompt, rows)?;
+    if app.config.read_only {
+        return Ok(());
+    }
+    let choices = vec![
+        Row::new(tr!("Postpone"), Choice::Postpone),
+        Row::new(tr!("Break down into subtasks"), Choice::BreakDown),
+        Row::new(tr!("Delete"), Choice::Delete),
+        Row::new(tr!("Back"), Choice::Back),
+    ];
+    match rich_rofi(task.description(), choices)? {
+        Choice::Postpone => {
+            let date = dates::pick_date(&tr!("Postpone until?"), &app.wait_presets(), &app.config)?;
+            task_command(vec![
+                &task.uuid().to_string(),
+                "mod",
+                &format!("wait:{}", date),
+            ])
+            .context(tr!("modifying wait"))?;
+        }
+        Choice::BreakDown => subtask::add_subtasks(&task)?,
+        Choice::Delete => {
+            let mut deleted = task.clone();
+            *deleted.status_mut() = TaskStatus::Deleted;
+            conflict::save(&task, &deleted, false)?;
+        }
+        Choice::Back => {}
+    }
+    Ok(())
+}
+
+fn last_touched(task: &Task) -> &Date {
+    task.modified().unwrap_or_else(|| task.entry())
+}
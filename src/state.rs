@@ -0,0 +1,78 @@
+//! Small persistent state kept between runs in `$XDG_STATE_HOME/taskwarrior-rofi/state.json`.
+
+use crate::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct State {
+    /// UUID of the task chosen with the Focus action.
+    pub focus: Option<String>,
+    /// UUIDs of the Today list, in the order they should be worked on.
+    pub today: Vec<String>,
+    /// Earlier inputs per prompt, most recent first.
+    pub history: BTreeMap<String, Vec<String>>,
+    /// Where the user is, matched against the `location` UDA by Here.
+    pub location: Option<String>,
+    /// The day (`YYYY-MM-DD`) the someday list was last offered for review.
+    pub someday_reviewed: Option<String>,
+    pub last_add: Option<LastAdd>,
+    /// Tasks `remind` leaves out, by UUID, until a Unix timestamp.
+    pub snoozed: BTreeMap<String, i64>,
+}
+
+/// The most recent Add, for catching the same text submitted twice in quick succession.
+#[derive(Serialize, Deserialize)]
+pub struct LastAdd {
+    pub text: String,
+    /// Unix timestamp.
+    pub at: i64,
+}
+
+/// How many inputs are kept for each prompt.
+const HISTORY_LENGTH: usize = 20;
+
+impl State {
+    pub fn load() -> Result<Self> {
+        let path = state_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).with_context(|| tr!("parsing {}", path.display()))
+    }
+
+    pub fn history(&self, prompt: &str) -> Vec<String> {
+        self.history.get(prompt).cloned().unwrap_or_default()
+    }
+
+    /// Moves the input to the front of the prompt's history and saves.
+    pub fn remember(&mut self, prompt: &str, input: &str) -> Result<()> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(());
+        }
+        let history = self.history.entry(prompt.to_string()).or_default();
+        history.retain(|entry| entry != input);
+        history.insert(0, input.to_string());
+        history.truncate(HISTORY_LENGTH);
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = state_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write then rename so an interrupted save can't leave a truncated file behind
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path).with_context(|| tr!("saving {}", path.display()))
+    }
+}
+
+fn state_path() -> Result<PathBuf> {
+    paths::state_file()
+}
@@ -0,0 +1,276 @@
+//! How rows are decorated, so the output works with plain fonts and without relying on color.
+//!
+//! ```toml
+//! [style]
+//! ascii = true          # no unicode glyphs
+//! emphasis = "letters"  # "color" (default), "letters" or "none"
+//! columns = false       # pad with spaces instead of tab-separated columns
+//! tab_stops = ["3em", "33em", "39em", "49em"]
+//! ```
+//!
+//! With letters, active tasks are prefixed `A`, overdue ones `O`, ones due today `D` and blocked
+//! ones `B`. With color, blocked tasks are greyed out and other unmarked tasks take the color of
+//! their taskwarrior `color.project.*` or `color.tag.*` rule.
+//!
+//! Columns are separated by tabs and lined up by rofi at `tab_stops` (the `tab-stops` theme
+//! property of rofi 1.7.2 and later), so proportional fonts don't throw them off. Older rofi
+//! versions ignore the property and show plain tabs.
+//!
+//! The heat indicator puts `·`, `•` or `●` (`.`, `o`, `O` with `ascii`) in front of each row,
+//! hotter as the due date gets closer or, with `by = "age"`, the longer the task has been
+//! pending. `levels` are the days at which each mark starts:
+//!
+//! ```toml
+//! [style.heat]
+//! enabled = true
+//! by = "due"           # or "age"
+//! levels = [7, 3, 1]   # due within 7, 3 and 1 days; overdue counts as due now
+//! ```
+//!
+//! For age the levels count up instead, by default `[14, 30, 90]`. The mark widens the first
+//! column, so the first tab stop may need moving. Tasks without a due date stay cold by due.
+
+use crate::colors::TaskColors;
+use chrono::{offset::Local, TimeZone, Utc};
+use serde::Deserialize;
+use std::borrow::Cow;
+use task_hookrs::task::Task;
+
+/// Colors of marked rows, shared with `theme export`.
+pub const ACTIVE_COLOR: &str = "#98c379";
+pub const OVERDUE_COLOR: &str = "#e06c75";
+pub const DUE_TODAY_COLOR: &str = "#e5c07b";
+pub const BLOCKED_COLOR: &str = "#7f848e";
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Style {
+    pub ascii: bool,
+    pub emphasis: Emphasis,
+    pub columns: bool,
+    pub tab_stops: Vec<String>,
+    pub heat: Heat,
+    /// Longer descriptions are cut short in task rows.
+    pub description_width: usize,
+    /// From taskrc rather than this config, loaded when emphasis is color.
+    #[serde(skip)]
+    pub task_colors: TaskColors,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            ascii: false,
+            emphasis: Emphasis::default(),
+            columns: true,
+            tab_stops: ["3em", "33em", "39em", "49em"]
+                .iter()
+                .map(|stop| stop.to_string())
+                .collect(),
+            heat: Heat::default(),
+            description_width: 60,
+            task_colors: TaskColors::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Emphasis {
+    #[default]
+    Color,
+    Letters,
+    None,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Heat {
+    pub enabled: bool,
+    pub by: HeatBy,
+    /// Days for each mark, coolest first. Empty means the defaults for `by`.
+    pub levels: Vec<i64>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HeatBy {
+    #[default]
+    Due,
+    Age,
+}
+
+const HEAT_GLYPHS: [Glyph; 3] = [Glyph::HeatLow, Glyph::HeatMedium, Glyph::HeatHigh];
+
+#[derive(Clone, Copy)]
+pub enum Glyph {
+    Rule,
+    ArrowLeft,
+    ArrowRight,
+    BarFull,
+    BarEmpty,
+    Play,
+    HeatLow,
+    HeatMedium,
+    HeatHigh,
+}
+
+#[derive(Clone, Copy)]
+enum Mark {
+    Active,
+    Overdue,
+    DueToday,
+    Blocked,
+}
+
+impl Style {
+    pub fn glyph(&self, glyph: Glyph) -> &'static str {
+        match (glyph, self.ascii) {
+            (Glyph::Rule, false) => "──",
+            (Glyph::Rule, true) => "--",
+            (Glyph::ArrowLeft, false) => "←",
+            (Glyph::ArrowLeft, true) => "<-",
+            (Glyph::ArrowRight, false) => "→",
+            (Glyph::ArrowRight, true) => "->",
+            (Glyph::BarFull, false) => "█",
+            (Glyph::BarFull, true) => "#",
+            (Glyph::BarEmpty, false) => "░",
+            (Glyph::BarEmpty, true) => "-",
+            (Glyph::Play, false) => "▶",
+            (Glyph::Play, true) => ">",
+            (Glyph::HeatLow, false) => "·",
+            (Glyph::HeatLow, true) => ".",
+            (Glyph::HeatMedium, false) => "•",
+            (Glyph::HeatMedium, true) => "o",
+            (Glyph::HeatHigh, false) => "●",
+            (Glyph::HeatHigh, true) => "O",
+        }
+    }
+
+    /// The heat mark for a task, a space when it is cold so rows stay aligned. `None` when the
+    /// indicator is off.
+    pub fn heat(&self, task: &Task) -> Option<&'static str> {
+        if !self.heat.enabled {
+            return None;
+        }
+        let levels = match (self.heat.levels.as_slice(), self.heat.by) {
+            ([], HeatBy::Due) => &[7, 3, 1][..],
+            ([], HeatBy::Age) => &[14, 30, 90][..],
+            (levels, _) => levels,
+        };
+        let now = Utc::now().naive_utc();
+        let reached = match self.heat.by {
+            HeatBy::Due => match task.due() {
+                Some(due) => {
+                    let days = (**due - now).num_days().max(0);
+                    levels.iter().filter(|level| days <= **level).count()
+                }
+                None => 0,
+            },
+            HeatBy::Age => {
+                let days = (now - **task.entry()).num_days();
+                levels.iter().filter(|level| days >= **level).count()
+            }
+        };
+        Some(match reached.min(HEAT_GLYPHS.len()) {
+            0 => " ",
+            level => self.glyph(HEAT_GLYPHS[level - 1]),
+        })
+    }
+
+    /// Whether labels from `emphasize` are pango markup.
+    pub fn markup(&self) -> bool {
+        self.emphasis == Emphasis::Color
+    }
+
+    /// Decorates a task's row label according to the task's state. `blocked` is whether it waits
+    /// on unfinished dependencies, which the task alone doesn't tell.
+    pub fn emphasize(&self, task: &Task, label: String, blocked: bool) -> String {
+        let mark = mark(task, blocked);
+        match (self.emphasis, mark) {
+            (Emphasis::Color, Some(mark)) => {
+                let color = match mark {
+                    Mark::Active => ACTIVE_COLOR,
+                    Mark::Overdue => OVERDUE_COLOR,
+                    Mark::DueToday => DUE_TODAY_COLOR,
+                    Mark::Blocked => BLOCKED_COLOR,
+                };
+                format!(
+                    "<span foreground=\"{}\">{}</span>",
+                    color,
+                    escape_markup(&label)
+                )
+            }
+            (Emphasis::Color, None) => match self.task_colors.accent(task) {
+                Some(accent) => {
+                    let mut attributes = vec![];
+                    if let Some(color) = &accent.color {
+                        attributes.push(format!("foreground=\"{}\"", color));
+                    }
+                    if accent.bold {
+                        attributes.push("weight=\"bold\"".to_string());
+                    }
+                    format!(
+                        "<span {}>{}</span>",
+                        attributes.join(" "),
+                        escape_markup(&label)
+                    )
+                }
+                // Most labels have nothing to escape, and can be passed on as they are
+                None => match escape_markup(&label) {
+                    Cow::Borrowed(_) => label,
+                    Cow::Owned(escaped) => escaped,
+                },
+            },
+            (Emphasis::Letters, mark) => {
+                let letter = match mark {
+                    Some(Mark::Active) => "A",
+                    Some(Mark::Overdue) => "O",
+                    Some(Mark::DueToday) => "D",
+                    Some(Mark::Blocked) => "B",
+                    None => " ",
+                };
+                format!("{} {}", letter, label)
+            }
+            (Emphasis::None, _) => label,
+        }
+    }
+}
+
+fn mark(task: &Task, blocked: bool) -> Option<Mark> {
+    if task.start().is_some() {
+        return Some(Mark::Active);
+    }
+    if let Some(due) = task.due() {
+        // Taskwarrior exports dates in UTC
+        let due = Local.from_utc_datetime(due);
+        let now = Local::now();
+        if due < now {
+            return Some(Mark::Overdue);
+        } else if due.date() == now.date() {
+            return Some(Mark::DueToday);
+        }
+    }
+    if blocked {
+        Some(Mark::Blocked)
+    } else {
+        None
+    }
+}
+
+/// The text with pango's special characters escaped, borrowed when there are none.
+pub fn escape_markup(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>']) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
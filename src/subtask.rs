@@ -0,0 +1,52 @@
+//! Lightweight hierarchical tasks: children inherit the parent's project and tags, and the
+//! parent depends on them.
+
+use crate::{create_task, task_command, widgets};
+use anyhow::{Context, Result};
+use task_hookrs::task::Task;
+
+pub fn add_subtasks(parent: &Task) -> Result<()> {
+    let mut inherited = vec![];
+    if let Some(project) = parent.project() {
+        inherited.push(format!("project:{}", project));
+    }
+    if let Some(tags) = parent.tags() {
+        inherited.extend(tags.iter().map(|tag| format!("+{}", tag)));
+    }
+
+    let mut children = vec![];
+    loop {
+        let prompt = tr!("Subtask {} of {}", children.len() + 1, parent.description());
+        let description = match widgets::text(&prompt) {
+            Ok(description) if !description.trim().is_empty() => description,
+            // Finish on an empty entry or escape, keeping whatever was already added
+            Ok(_) | Err(rofi::Error::Blank) | Err(rofi::Error::Interrupted) => break,
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut args = vec!["add"];
+        args.extend(description.split_whitespace());
+        args.extend(inherited.iter().map(String::as_str));
+        children.push(create_task(args).context(tr!("adding subtask"))?);
+    }
+
+    if children.is_empty() {
+        return Ok(());
+    }
+
+    let mut depends: Vec<_> = parent
+        .depends()
+        .into_iter()
+        .flatten()
+        .map(|uuid| uuid.to_string())
+        .collect();
+    depends.extend(children);
+    task_command(vec![
+        &parent.uuid().to_string(),
+        "mod",
+        &format!("depends:{}", depends.join(",")),
+    ])
+    .context(tr!("linking subtasks to parent"))?;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+//! The Sync action: `task sync`, retried with backoff when the network is the problem.
+//!
+//! Failures are sorted by what taskwarrior printed. Timeouts and unreachable servers are retried
+//! (`[sync] attempts` and `backoff`). Rejected credentials and certificate problems won't fix
+//! themselves, so they stop right away with a hint about which settings to check. Anything else
+//! is reported as it is. Either way the end is a report of every attempt.
+
+use crate::{
+    config::SyncConfig,
+    probe,
+    widgets::{rich_rofi, Row},
+};
+use anyhow::{bail, Context, Result};
+use std::{process::Command, thread, time::Duration};
+
+/// Lowercased snippets of `task sync` output that mean the server couldn't be reached in time.
+const TRANSIENT: [&str; 10] = [
+    "timed out",
+    "timeout",
+    "connection refused",
+    "connection reset",
+    "could not connect",
+    "network is unreachable",
+    "no route to host",
+    "temporary failure in name resolution",
+    "could not resolve",
+    "503",
+];
+/// Lowercased snippets that mean the server turned us away.
+const AUTH: [&str; 10] = [
+    "certificate",
+    "handshake",
+    "x509",
+    "credentials",
+    "not authorized",
+    "unauthorized",
+    "invalid username",
+    "401",
+    "403",
+    "encryption_secret",
+];
+
+enum Failure {
+    Transient,
+    Auth,
+    Other,
+}
+
+pub fn sync(config: &SyncConfig) -> Result<()> {
+    if !probe::capabilities().sync_configured() {
+        bail!(probe::sync_fix());
+    }
+
+    let mut report = vec![];
+    let mut wait = config.backoff;
+    let attempts = config.attempts.max(1);
+    for attempt in 1..=attempts {
+        let output = Command::new("task")
+            .arg("sync")
+            .output()
+            .context(tr!("running task sync"))?;
+        if output.status.success() {
+            report.push(tr!("Attempt {}: synced", attempt));
+            break;
+        }
+
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stderr),
+            String::from_utf8_lossy(&output.stdout)
+        );
+        let summary = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string();
+        match classify(&text) {
+            Failure::Transient if attempt < attempts => {
+                report.push(tr!(
+                    "Attempt {}: {} (retrying in {}s)",
+                    attempt,
+                    summary,
+                    wait
+                ));
+                thread::sleep(Duration::from_secs(wait));
+                wait *= 2;
+            }
+            Failure::Transient => {
+                report.push(tr!("Attempt {}: {}", attempt, summary));
+                report.push(tr!(
+                    "Gave up after {} attempts; is the server reachable?",
+                    attempts
+                ));
+            }
+            Failure::Auth => {
+                report.push(tr!("Attempt {}: {}", attempt, summary));
+                report.push(auth_fix());
+                break;
+            }
+            Failure::Other => {
+                report.push(tr!("Attempt {}: {}", attempt, summary));
+                break;
+            }
+        }
+    }
+
+    let rows: Vec<Row<()>> = report.into_iter().map(|line| Row::new(line, ())).collect();
+    rich_rofi::<_, ()>(&tr!("Sync"), rows)?;
+    Ok(())
+}
+
+fn classify(output: &str) -> Failure {
+    let output = output.to_lowercase();
+    // Checked first: a TLS handshake can also end with a timeout message
+    if AUTH.iter().any(|snippet| output.contains(snippet)) {
+        Failure::Auth
+    } else if TRANSIENT.iter().any(|snippet| output.contains(snippet)) {
+        Failure::Transient
+    } else {
+        Failure::Other
+    }
+}
+
+/// Which taskrc settings hold the credentials, for the installed version.
+fn auth_fix() -> String {
+    if probe::capabilities().version.0 >= 3 {
+        tr!("The server rejected this client; check sync.server.client_id and sync.encryption_secret in taskrc")
+    } else {
+        tr!("The server rejected this client; check taskd.credentials, taskd.certificate, taskd.key and taskd.ca in taskrc")
+    }
+}
@@ -0,0 +1,58 @@
+//! `theme export`: a rofi theme snippet styled for the rows this tool draws, to paste into a theme
+//! or `@import` from one.
+//!
+//! Active rows (like the focused task) take the active color and urgent ones (like an overloaded
+//! day) the overdue color, matching the pango markup task rows get. With columns on, the tab stops
+//! from `[style]` are included so the snippet works without the `-theme-str` this tool passes.
+
+use crate::style::{Style, ACTIVE_COLOR, BLOCKED_COLOR, DUE_TODAY_COLOR, OVERDUE_COLOR};
+
+/// Text on a selected active or urgent row, dark enough for any of the mark colors.
+const SELECTED_TEXT_COLOR: &str = "#282c34";
+
+pub fn export(style: &Style) -> String {
+    let mut rasi = format!(
+        "/* Generated by `{name} theme export` */\n\n\
+         * {{\n    \
+         tw-active: {active};\n    \
+         tw-overdue: {overdue};\n    \
+         tw-due-today: {due_today};\n    \
+         tw-blocked: {blocked};\n    \
+         tw-selected-text: {selected};\n\
+         }}\n",
+        name = env!("CARGO_PKG_NAME"),
+        active = ACTIVE_COLOR,
+        overdue = OVERDUE_COLOR,
+        due_today = DUE_TODAY_COLOR,
+        blocked = BLOCKED_COLOR,
+        selected = SELECTED_TEXT_COLOR
+    );
+    if style.markup() {
+        rasi.push_str("\nconfiguration {\n    markup-rows: true;\n}\n");
+    }
+
+    // Without inheriting, rofi 1.7 themes keep element-text's own colors over the state ones
+    rasi.push_str("\nelement-text {\n    background-color: inherit;\n    text-color: inherit;\n");
+    if style.columns && !style.tab_stops.is_empty() {
+        rasi.push_str(&format!(
+            "    tab-stops: [{}];\n",
+            style.tab_stops.join(", ")
+        ));
+    }
+    rasi.push_str("}\n");
+
+    for (state, color) in [("active", "@tw-active"), ("urgent", "@tw-overdue")] {
+        rasi.push_str(&format!(
+            "\nelement normal.{state}, element alternate.{state} {{\n    \
+             text-color: {color};\n\
+             }}\n\
+             element selected.{state} {{\n    \
+             background-color: {color};\n    \
+             text-color: @tw-selected-text;\n\
+             }}\n",
+            state = state,
+            color = color
+        ));
+    }
+    rasi
+}
@@ -0,0 +1,122 @@
+//! A hand-ordered list of tasks to work through today, kept in the state file.
+
+use crate::{
+    conflict, export, format_task,
+    index::TaskIndex,
+    state::State,
+    task_rofi,
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::Result;
+use chrono::offset::Local as LocalTime;
+use task_hookrs::{status::TaskStatus, task::Task};
+
+enum Entry {
+    Task(usize),
+    Add,
+}
+
+enum Choice {
+    Start,
+    Done,
+    MoveUp,
+    MoveDown,
+    Remove,
+    Back,
+}
+
+pub fn today(app: &mut App) -> Result<()> {
+    loop {
+        let tasks = today_tasks(&mut app.state)?;
+
+        let mut rows: Vec<_> = tasks
+            .iter()
+            .enumerate()
+            .map(|(idx, task)| {
+                Row::new(
+                    format!("{:>2}. {}", idx + 1, format_task(task, &app.config.style)),
+                    Entry::Task(idx),
+                )
+                .active(task.start().is_some())
+            })
+            .collect();
+        rows.push(Row::new(tr!("+ Add a task to today"), Entry::Add));
+
+        let idx = match rich_rofi(&tr!("Today"), rows)? {
+            Entry::Add => {
+                let task = task_rofi(&tr!("Add to today"), app)?;
+                let uuid = task.uuid().to_string();
+                if !app.state.today.contains(&uuid) {
+                    app.state.today.push(uuid);
+                    app.state.save()?;
+                }
+                continue;
+            }
+            Entry::Task(idx) => idx,
+        };
+
+        let mut choices = vec![];
+        if !app.config.read_only {
+            choices.push(("Start", Choice::Start));
+            choices.push(("Done", Choice::Done));
+        }
+        // The list itself is this tool's own state, so read-only menus still rearrange it
+        choices.extend(vec![
+            ("Move up", Choice::MoveUp),
+            ("Move down", Choice::MoveDown),
+            ("Remove from today", Choice::Remove),
+            ("Back", Choice::Back),
+        ]);
+        let choices = choices
+            .into_iter()
+            .map(|(label, item)| Row::new(tr!(label), item))
+            .collect();
+
+        let state = &mut app.state;
+        let mut task = tasks[idx].clone();
+        match rich_rofi(task.description(), choices)? {
+            Choice::Start => {
+                task.set_start(Some(LocalTime::now().naive_local()));
+                conflict::save(&tasks[idx], &task, false)?;
+            }
+            Choice::Done => {
+                *task.status_mut() = TaskStatus::Completed;
+                if let conflict::Outcome::Saved = conflict::save(&tasks[idx], &task, false)? {
+                    state.today.remove(idx);
+                }
+            }
+            Choice::MoveUp if idx > 0 => state.today.swap(idx, idx - 1),
+            Choice::MoveDown if idx + 1 < state.today.len() => state.today.swap(idx, idx + 1),
+            Choice::Remove => {
+                state.today.remove(idx);
+            }
+            Choice::MoveUp | Choice::MoveDown | Choice::Back => {}
+        }
+        state.save()?;
+    }
+}
+
+/// Resolves the saved list to pending tasks, dropping any that were finished or deleted
+/// elsewhere so indices into `state.today` and the returned tasks line up.
+fn today_tasks(state: &mut State) -> Result<Vec<Task>> {
+    if state.today.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let found = TaskIndex::new(export::query(&state.today.join(" "))?);
+    let mut tasks = vec![];
+    let before = state.today.len();
+    state.today.retain(|uuid| match found.get(uuid) {
+        Some(task) if *task.status() == TaskStatus::Pending => {
+            tasks.push(task.clone());
+            true
+        }
+        _ => false,
+    });
+    if state.today.len() != before {
+        state.save()?;
+    }
+
+    Ok(tasks)
+}
@@ -0,0 +1,210 @@
+//! Taskwarrior's urgency formula for the terms an edit can change (due, priority, project and
+//! tags), so the effect of a change can be shown before it is made.
+//!
+//! The other terms, like age or blocking, are left to taskwarrior: the preview takes the task's
+//! current urgency, subtracts these terms as they are and adds them as they would be.
+
+use crate::{dates::split_setting, task_command};
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use std::collections::BTreeMap;
+use task_hookrs::{priority::TaskPriority, task::Task};
+
+pub struct Coefficients(BTreeMap<String, f64>);
+
+impl Coefficients {
+    /// The `urgency.*` settings from taskrc, defaults included.
+    pub fn load() -> Result<Self> {
+        let (settings, _) = task_command(vec!["_show"])?;
+        Ok(Self(
+            settings
+                .lines()
+                .filter_map(split_setting)
+                .filter(|(key, _)| key.starts_with("urgency."))
+                .filter_map(|(key, value)| Some((key.to_string(), value.trim().parse().ok()?)))
+                .collect(),
+        ))
+    }
+
+    fn get(&self, key: &str) -> f64 {
+        self.0.get(key).copied().unwrap_or(0.0)
+    }
+
+    /// `urgency.user.<kind>.<name>.coefficient` entries, as name and coefficient.
+    fn user(&self, kind: &str) -> impl Iterator<Item = (&str, f64)> {
+        let prefix = format!("urgency.user.{}.", kind);
+        self.0.iter().filter_map(move |(key, value)| {
+            let name = key.strip_prefix(&prefix)?.strip_suffix(".coefficient")?;
+            Some((name, *value))
+        })
+    }
+}
+
+/// The urgency `changed` would have, given `task`'s current one.
+pub fn preview(task: &Task, changed: &Task, coefficients: &Coefficients) -> f64 {
+    let now = Utc::now().naive_utc();
+    task.urgency().unwrap_or(0.0) - editable_terms(task, coefficients, now)
+        + editable_terms(changed, coefficients, now)
+}
+
+fn editable_terms(task: &Task, coefficients: &Coefficients, now: NaiveDateTime) -> f64 {
+    let mut urgency = 0.0;
+
+    if let Some(due) = task.due() {
+        urgency += coefficients.get("urgency.due.coefficient") * due_factor(**due, now);
+    }
+    if let Some(priority) = task.priority() {
+        let level = match priority {
+            TaskPriority::High => "H",
+            TaskPriority::Medium => "M",
+            TaskPriority::Low => "L",
+        };
+        urgency += coefficients.get(&format!("urgency.uda.priority.{}.coefficient", level));
+    }
+
+    if let Some(project) = task.project() {
+        urgency += coefficients.get("urgency.project.coefficient");
+        urgency += coefficients
+            .user("project")
+            .filter(|(name, _)| project.starts_with(name))
+            .map(|(_, coefficient)| coefficient)
+            .sum::<f64>();
+    }
+
+    let tags = task.tags().cloned().unwrap_or_default();
+    urgency += coefficients.get("urgency.tags.coefficient") * count_factor(tags.len());
+    urgency += coefficients
+        .user("tag")
+        .filter(|(name, _)| tags.iter().any(|tag| tag == name))
+        .map(|(_, coefficient)| coefficient)
+        .sum::<f64>();
+
+    urgency
+}
+
+/// Rises from 0.2 two weeks before the due date to 1.0 a week after it.
+fn due_factor(due: NaiveDateTime, now: NaiveDateTime) -> f64 {
+    let days_overdue = (now - due).num_seconds() as f64 / 86400.0;
+    if days_overdue >= 7.0 {
+        1.0
+    } else if days_overdue >= -14.0 {
+        (days_overdue + 14.0) * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+fn count_factor(count: usize) -> f64 {
+    match count {
+        0 => 0.0,
+        1 => 0.8,
+        2 => 0.9,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+    use serde_json::{json, Value};
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 14)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    fn coefficients() -> Coefficients {
+        Coefficients(
+            vec![
+                ("urgency.due.coefficient", 12.0),
+                ("urgency.uda.priority.H.coefficient", 6.0),
+                ("urgency.uda.priority.M.coefficient", 3.9),
+                ("urgency.uda.priority.L.coefficient", 1.8),
+                ("urgency.project.coefficient", 1.0),
+                ("urgency.tags.coefficient", 1.0),
+                ("urgency.user.project.Home.coefficient", 2.0),
+                ("urgency.user.tag.next.coefficient", 15.0),
+            ]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+        )
+    }
+
+    fn task(fields: Value) -> Task {
+        let mut task = json!({
+            "uuid": "8ca953d5-18b6-4eb1-a2b3-f38f1b6d8b9a",
+            "description": "Fix the fence",
+            "status": "pending",
+            "entry": "20261001T090000Z",
+        });
+        task.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(task).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn due_factor_ramps_over_three_weeks() {
+        assert_close(due_factor(now() + Duration::days(30), now()), 0.2);
+        assert_close(due_factor(now() + Duration::days(14), now()), 0.2);
+        assert_close(due_factor(now(), now()), 14.0 * 0.8 / 21.0 + 0.2);
+        assert_close(due_factor(now() - Duration::days(7), now()), 1.0);
+        assert_close(due_factor(now() - Duration::days(30), now()), 1.0);
+    }
+
+    #[test]
+    fn count_factor_levels_off() {
+        let factors: Vec<_> = (0..5).map(count_factor).collect();
+        assert_eq!(factors, vec![0.0, 0.8, 0.9, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn bare_task_has_no_editable_terms() {
+        assert_close(
+            editable_terms(&task(json!({})), &coefficients(), now()),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn priority_levels() {
+        for (priority, expected) in [("H", 6.0), ("M", 3.9), ("L", 1.8)] {
+            let task = task(json!({ "priority": priority }));
+            assert_close(editable_terms(&task, &coefficients(), now()), expected);
+        }
+    }
+
+    #[test]
+    fn user_coefficients_match_project_prefixes_and_whole_tags() {
+        let task = task(json!({ "project": "Home.garden", "tags": ["next", "nextweek"] }));
+        // project, Home, two tags at 0.9, and next but not nextweek
+        assert_close(
+            editable_terms(&task, &coefficients(), now()),
+            1.0 + 2.0 + 0.9 + 15.0,
+        );
+    }
+
+    #[test]
+    fn preview_swaps_the_editable_terms() {
+        let before = task(json!({ "urgency": 10.0, "priority": "L" }));
+        let after = task(json!({ "urgency": 10.0, "priority": "H", "due": "20261001T090000Z" }));
+        // Due two weeks ago counts in full
+        assert_close(
+            preview(&before, &after, &coefficients()),
+            10.0 - 1.8 + 6.0 + 12.0,
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Notes kept in an Obsidian vault (or any folder of Markdown files), linked from tasks by
+//! wiki-name in a UDA. Open turns the link into an `obsidian://` URL.
+//!
+//! ```toml
+//! [vault]
+//! name = "Work"            # the vault's name in Obsidian
+//! path = "~/vaults/work"
+//! uda = "note"             # needs uda.note.type=string in taskrc
+//! ```
+
+use crate::{
+    config::VaultConfig, get_config_var, paths::expand_home, task_command, widgets::rich_rofi,
+    TaskExt,
+};
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path};
+use task_hookrs::task::Task;
+
+/// Picks a note from the vault and stores its wiki-name on the task.
+pub fn link_note(task: &Task, config: &VaultConfig) -> Result<()> {
+    let path = match &config.path {
+        Some(path) => expand_home(path)?,
+        None => bail!(tr!("Set [vault] path in the config to link notes")),
+    };
+    if get_config_var(&format!("uda.{}.type", config.uda)).is_err() {
+        bail!(tr!(
+            "Define the {} UDA in taskrc first: uda.{}.type=string",
+            config.uda,
+            config.uda
+        ));
+    }
+
+    let mut names = vec![];
+    collect_notes(&path, &path, &mut names)?;
+    if names.is_empty() {
+        bail!(tr!("No notes found in {}", path.display()));
+    }
+    names.sort();
+
+    let name: String = rich_rofi(&tr!("Link note"), names)?;
+    task_command(vec![
+        &task.uuid().to_string(),
+        "mod",
+        &format!("{}:{}", config.uda, name),
+    ])
+    .context(tr!("linking note"))?;
+    Ok(())
+}
+
+/// The `obsidian://` URL for the task's linked note, if it has one.
+pub fn note_url(task: &Task, config: &VaultConfig) -> Option<String> {
+    let vault = config.name.as_ref()?;
+    let note = task.uda_string(&config.uda)?;
+    Some(format!(
+        "obsidian://open?vault={}&file={}",
+        encode(vault),
+        encode(&note)
+    ))
+}
+
+/// Wiki-names of every Markdown file under `dir`: the path relative to the vault, without `.md`.
+/// Hidden directories like `.obsidian` are skipped.
+fn collect_notes(root: &Path, dir: &Path, names: &mut Vec<String>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| tr!("reading {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_notes(root, &path, names)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            let relative = path.strip_prefix(root)?.with_extension("");
+            let parts: Vec<_> = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            names.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+/// Percent-encodes everything but unreserved URL characters.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
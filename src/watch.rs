@@ -0,0 +1,208 @@
+//! `--watch`: after the menu closes, wait for the task data to change (or for a ping) and open it
+//! again, for a menu that lives on a dedicated monitor or scratchpad.
+//!
+//! Changes are noticed by polling the modification times in taskwarrior's `data.location`. A ping
+//! is any connection to the socket, which `taskwarrior-rofi ping` makes, so a hotkey can bring the
+//! menu back without anything changing.
+//!
+//! Connections can also send one line with a request, so other programs can drive a running
+//! watcher without starting a menu of their own:
+//!
+//! - `show` (or nothing) reopens the menu
+//! - `add <text>` adds a task, with `--` separating annotations as in Add
+//! - `done <uuid>` completes a task
+//! - `active` answers with the UUID and description of each started task, one per line
+//!
+//! Anything but `show` gets an answer: the output, or `error: ` and a message. With the `dbus`
+//! feature the watcher also serves the same on the session bus (see `dbus`).
+
+use crate::{get_config_var, paths};
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Connections are answered one at a time, so a client that stops talking is dropped after this.
+#[cfg(unix)]
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the watcher woke up for. Only the main thread opens rofi, so the socket and D-Bus
+/// listeners send one of these instead.
+pub enum Wake {
+    /// The task data changed or something asked for the menu
+    Menu,
+    /// A desktop search result was picked, for that task's Start/Done menu
+    #[cfg(feature = "search-provider")]
+    Task(String),
+}
+
+/// Started once, before the first menu, so pings sent while a menu is open aren't lost.
+pub struct Watcher {
+    data_dir: PathBuf,
+    wakes: Receiver<Wake>,
+}
+
+impl Watcher {
+    pub fn start() -> Result<Self> {
+        let data_dir = paths::expand_home(&get_config_var("data.location")?)?;
+        let (sender, wakes) = mpsc::channel();
+        #[cfg(feature = "dbus")]
+        {
+            // The socket still works without a session bus, as on a headless machine
+            if let Err(err) = crate::dbus::start(sender.clone()) {
+                eprintln!("{}", tr!("Not serving D-Bus: {}", format!("{:#}", err)));
+            }
+        }
+        listen(sender)?;
+        Ok(Self { data_dir, wakes })
+    }
+
+    /// Blocks until the data directory changes or something wakes the watcher.
+    pub fn wait(&self) -> Result<Wake> {
+        // Pings from while the menu was open were answered by the menu closing, but a search
+        // result picked meanwhile still wants its menu
+        while let Ok(wake) = self.wakes.try_recv() {
+            if !matches!(wake, Wake::Menu) {
+                return Ok(wake);
+            }
+        }
+        let before = last_modified(&self.data_dir)?;
+        loop {
+            match self.wakes.recv_timeout(POLL_INTERVAL) {
+                Ok(wake) => return Ok(wake),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+            if last_modified(&self.data_dir)? != before {
+                return Ok(Wake::Menu);
+            }
+        }
+    }
+}
+
+/// Whether requests to the watcher may change tasks.
+pub fn read_only() -> Result<bool> {
+    Ok(crate::config::Config::load()?.read_only || crate::cli::read_only())
+}
+
+pub fn last_modified(dir: &Path) -> Result<Option<SystemTime>> {
+    let entries = fs::read_dir(dir).with_context(|| tr!("reading {}", dir.display()))?;
+    Ok(entries
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max())
+}
+
+#[cfg(unix)]
+fn listen(sender: mpsc::Sender<Wake>) -> Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    let path = paths::socket_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Left behind by a watcher that didn't shut down cleanly
+    let _ = fs::remove_file(&path);
+    let listener =
+        UnixListener::bind(&path).with_context(|| tr!("listening on {}", path.display()))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if serve(stream) && sender.send(Wake::Menu).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Answers one connection, returning whether it asked for the menu.
+#[cfg(unix)]
+fn serve(mut stream: std::os::unix::net::UnixStream) -> bool {
+    use std::io::{BufRead, BufReader, Write};
+
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let _ = stream.set_write_timeout(Some(TIMEOUT));
+    let mut line = String::new();
+    if BufReader::new(&stream).read_line(&mut line).is_err() {
+        return true;
+    }
+    let line = line.trim();
+    if line.is_empty() || line == "show" {
+        return true;
+    }
+    let reply = match request(line) {
+        Ok(output) => output,
+        Err(err) => format!("error: {}\n", err),
+    };
+    let _ = stream.write_all(reply.as_bytes());
+    false
+}
+
+#[cfg(unix)]
+fn request(line: &str) -> Result<String> {
+    use crate::{add_task_quietly, is_uuid, query_sorted, split_add_input, task_command};
+
+    let (command, argument) = match line.split_once(' ') {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    if matches!(command, "add" | "done") && read_only()? {
+        bail!(tr!("This menu is read-only"));
+    }
+    match (command, argument) {
+        ("add", text) if !text.is_empty() => {
+            let (task_text, annotations) = split_add_input(text)?;
+            add_task_quietly(&task_text, &annotations)?;
+        }
+        // Anything else there would be a filter, which could match every task
+        ("done", uuid) if is_uuid(uuid) => {
+            task_command(vec![uuid, "done"]).context(tr!("completing task"))?;
+        }
+        ("active", "") => {
+            return Ok(query_sorted("+ACTIVE")?
+                .iter()
+                .map(|task| format!("{} {}\n", task.uuid(), task.description()))
+                .collect());
+        }
+        _ => bail!(tr!("unknown request `{}`", line)),
+    }
+    Ok("ok\n".to_string())
+}
+
+#[cfg(not(unix))]
+fn listen(_sender: mpsc::Sender<Wake>) -> Result<()> {
+    Ok(())
+}
+
+/// Sends a request (empty for `show`) to the watcher, returning its answer.
+#[cfg(unix)]
+pub fn ping(request: &str) -> Result<String> {
+    use std::{
+        io::{Read, Write},
+        net::Shutdown,
+    };
+
+    let path = paths::socket_file()?;
+    let mut stream = std::os::unix::net::UnixStream::connect(&path)
+        .with_context(|| tr!("no watcher listening on {}", path.display()))?;
+    writeln!(stream, "{}", request)?;
+    stream.shutdown(Shutdown::Write)?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    if let Some(err) = reply.strip_prefix("error: ") {
+        bail!("{}", err.trim());
+    }
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+pub fn ping(_request: &str) -> Result<String> {
+    bail!(tr!("ping needs Unix sockets"))
+}
@@ -0,0 +1,142 @@
+//! POSTing each change made through this tool to a webhook, for automation like Home Assistant or
+//! n8n.
+//!
+//! ```toml
+//! [webhook]
+//! url = "https://n8n.example.com/webhook/tasks"
+//! timeout = 5   # seconds
+//! ```
+//!
+//! Payloads are JSON objects with an `event` of `command` (with the `task` arguments) or `save`
+//! (with the saved task), plus `at`, an RFC 3339 timestamp. Deliveries that fail are queued in
+//! the state directory and sent, in order, before the next one. Posting is done with `curl`.
+//!
+//! With the `dbus` feature the same payloads also go out as the `Mutated` signal (see `dbus`),
+//! whether or not a URL is set.
+
+use crate::{command_word, config::WebhookConfig, paths};
+use anyhow::{bail, Context, Result};
+use chrono::offset::Local as LocalTime;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+use task_hookrs::task::Task;
+
+/// `task` subcommands that change something.
+const MUTATING: [&str; 12] = [
+    "add",
+    "log",
+    "mod",
+    "modify",
+    "annotate",
+    "denotate",
+    "done",
+    "delete",
+    "start",
+    "stop",
+    "duplicate",
+    "import",
+];
+
+static CONFIG: OnceLock<WebhookConfig> = OnceLock::new();
+
+pub fn init(config: &WebhookConfig) {
+    if config.url.is_some() {
+        let _ = CONFIG.set(config.clone());
+    }
+}
+
+/// Reports a `task` command, if it is one that changes something.
+pub fn command(args: &[&str]) {
+    if command_word(args).is_some_and(|word| MUTATING.contains(&word)) {
+        notify(json!({ "event": "command", "args": args }));
+    }
+}
+
+/// Reports tasks saved whole.
+pub fn saved(tasks: &[&Task]) {
+    for task in tasks {
+        notify(json!({ "event": "save", "task": task }));
+    }
+}
+
+/// A failed delivery never fails the change itself; it waits in the queue for the next one.
+fn notify(mut payload: Value) {
+    payload["at"] = json!(LocalTime::now().to_rfc3339());
+    #[cfg(feature = "dbus")]
+    crate::dbus::mutated(&payload);
+    let config = match CONFIG.get() {
+        Some(config) => config,
+        None => return,
+    };
+    let _ = deliver(config, payload);
+}
+
+fn deliver(config: &WebhookConfig, payload: Value) -> Result<()> {
+    let path = queue_file()?;
+    let mut queue: Vec<Value> = match fs::read_to_string(&path) {
+        Ok(contents) => contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect(),
+        Err(_) => vec![],
+    };
+    queue.push(payload);
+
+    let url = config.url.as_deref().unwrap_or_default();
+    let sent = queue
+        .iter()
+        .take_while(|payload| post(url, config.timeout, payload).is_ok())
+        .count();
+    queue.drain(..sent);
+
+    if queue.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let lines: Vec<_> = queue.iter().map(Value::to_string).collect();
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, lines.join("\n") + "\n")?;
+    fs::rename(&tmp, &path).with_context(|| tr!("saving {}", path.display()))
+}
+
+fn post(url: &str, timeout: u64, payload: &Value) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["-sSf", "-o", "/dev/null", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg("-m")
+        .arg(timeout.to_string())
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context(tr!("running curl"))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(payload.to_string().as_bytes())?;
+    if !child.wait()?.success() {
+        bail!(tr!("posting to {} failed", url));
+    }
+    Ok(())
+}
+
+fn queue_file() -> Result<PathBuf> {
+    Ok(paths::state_dir()?.join("webhook-queue.jsonl"))
+}
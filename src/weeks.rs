@@ -0,0 +1,194 @@
+//! Where weeks start, and which week a date falls in, for the calendar, the planner and the
+//! workload view.
+//!
+//! `week_start = "sunday"` in the config sets the first day. Without it the region of the locale
+//! decides: Sunday for the likes of `en_US` and `ja_JP`, Monday elsewhere.
+
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::{env, sync::OnceLock};
+
+static FIRST_DAY: OnceLock<Weekday> = OnceLock::new();
+
+/// Regions where calendars usually start on Sunday.
+const SUNDAY_REGIONS: [&str; 16] = [
+    "US", "CA", "MX", "BR", "AR", "CO", "PE", "VE", "JP", "KR", "TW", "HK", "PH", "IL", "IN", "ZA",
+];
+
+#[derive(PartialEq)]
+pub enum Bucket {
+    Earlier,
+    ThisWeek,
+    NextWeek,
+    Later,
+}
+
+impl Bucket {
+    pub fn label(&self) -> String {
+        match self {
+            Bucket::Earlier => tr!("Earlier"),
+            Bucket::ThisWeek => tr!("This week"),
+            Bucket::NextWeek => tr!("Next week"),
+            Bucket::Later => tr!("Later"),
+        }
+    }
+}
+
+pub fn init(config: &Config) -> Result<()> {
+    let first = match &config.week_start {
+        Some(day) => day
+            .parse()
+            .map_err(|_| anyhow!(tr!("week_start `{}` is not a day of the week", day)))?,
+        None => from_locale(config.locale.as_deref()),
+    };
+    let _ = FIRST_DAY.set(first);
+    Ok(())
+}
+
+fn from_locale(locale: Option<&str>) -> Weekday {
+    let locale = match locale {
+        Some(locale) => locale.to_string(),
+        None => ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default(),
+    };
+    // Like `en_US.UTF-8` or `pt_BR@euro`
+    let region = locale
+        .split(['.', '@'])
+        .next()
+        .and_then(|name| name.split('_').nth(1))
+        .unwrap_or_default();
+    if SUNDAY_REGIONS.contains(&region) {
+        Weekday::Sun
+    } else {
+        Weekday::Mon
+    }
+}
+
+/// Monday until `init` says otherwise.
+pub fn first_day() -> Weekday {
+    FIRST_DAY.get().copied().unwrap_or(Weekday::Mon)
+}
+
+/// The first day of the week `date` is in, for weeks starting on `first`.
+pub fn start_of_week(date: NaiveDate, first: Weekday) -> NaiveDate {
+    let offset = (date.weekday().num_days_from_monday() + 7 - first.num_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
+
+/// The last day of the week `date` is in.
+pub fn end_of_week(date: NaiveDate, first: Weekday) -> NaiveDate {
+    start_of_week(date, first) + Duration::days(6)
+}
+
+/// The seven days of a week in order, starting with `first`.
+pub fn days(first: Weekday) -> impl Iterator<Item = Weekday> {
+    std::iter::successors(Some(first), |day| Some(day.succ())).take(7)
+}
+
+pub fn bucket(date: NaiveDate, today: NaiveDate, first: Weekday) -> Bucket {
+    let this_week = start_of_week(today, first);
+    if date < this_week {
+        Bucket::Earlier
+    } else if date < this_week + Duration::days(7) {
+        Bucket::ThisWeek
+    } else if date < this_week + Duration::days(14) {
+        Bucket::NextWeek
+    } else {
+        Bucket::Later
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: &str) -> NaiveDate {
+        day.parse().unwrap()
+    }
+
+    // 2026-10-14 is a Wednesday
+
+    #[test]
+    fn week_starting_monday() {
+        assert_eq!(
+            start_of_week(date("2026-10-14"), Weekday::Mon),
+            date("2026-10-12")
+        );
+        assert_eq!(
+            start_of_week(date("2026-10-12"), Weekday::Mon),
+            date("2026-10-12")
+        );
+        assert_eq!(
+            start_of_week(date("2026-10-18"), Weekday::Mon),
+            date("2026-10-12")
+        );
+        assert_eq!(
+            end_of_week(date("2026-10-14"), Weekday::Mon),
+            date("2026-10-18")
+        );
+    }
+
+    #[test]
+    fn week_starting_sunday() {
+        assert_eq!(
+            start_of_week(date("2026-10-14"), Weekday::Sun),
+            date("2026-10-11")
+        );
+        assert_eq!(
+            start_of_week(date("2026-10-11"), Weekday::Sun),
+            date("2026-10-11")
+        );
+        assert_eq!(
+            start_of_week(date("2026-10-17"), Weekday::Sun),
+            date("2026-10-11")
+        );
+        assert_eq!(
+            end_of_week(date("2026-10-14"), Weekday::Sun),
+            date("2026-10-17")
+        );
+    }
+
+    #[test]
+    fn days_start_on_the_first_day() {
+        let days: Vec<_> = days(Weekday::Sun).collect();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0], Weekday::Sun);
+        assert_eq!(days[6], Weekday::Sat);
+    }
+
+    #[test]
+    fn buckets_around_monday_start() {
+        let today = date("2026-10-14");
+        let bucket = |day| bucket(date(day), today, Weekday::Mon);
+        assert!(bucket("2026-10-11") == Bucket::Earlier);
+        assert!(bucket("2026-10-12") == Bucket::ThisWeek);
+        assert!(bucket("2026-10-18") == Bucket::ThisWeek);
+        assert!(bucket("2026-10-19") == Bucket::NextWeek);
+        assert!(bucket("2026-10-25") == Bucket::NextWeek);
+        assert!(bucket("2026-10-26") == Bucket::Later);
+    }
+
+    #[test]
+    fn buckets_around_sunday_start() {
+        let today = date("2026-10-14");
+        let bucket = |day| bucket(date(day), today, Weekday::Sun);
+        assert!(bucket("2026-10-10") == Bucket::Earlier);
+        assert!(bucket("2026-10-11") == Bucket::ThisWeek);
+        assert!(bucket("2026-10-17") == Bucket::ThisWeek);
+        assert!(bucket("2026-10-18") == Bucket::NextWeek);
+        assert!(bucket("2026-10-24") == Bucket::NextWeek);
+        assert!(bucket("2026-10-25") == Bucket::Later);
+    }
+
+    #[test]
+    fn today_on_the_first_day_is_this_week() {
+        let sunday = date("2026-10-11");
+        assert!(bucket(sunday, sunday, Weekday::Sun) == Bucket::ThisWeek);
+        assert!(bucket(sunday, sunday, Weekday::Mon) == Bucket::ThisWeek);
+        assert!(bucket(date("2026-10-12"), sunday, Weekday::Mon) == Bucket::NextWeek);
+    }
+}
@@ -0,0 +1,309 @@
+//! The building blocks every action is made of: pickers, free-text prompts, validated prompts,
+//! confirmations and multi-selects. Escape comes back as `rofi::Error::Interrupted` from all of
+//! them, so callers decide whether it means "back" or "cancel".
+//!
+//! The parts that don't talk to rofi (labels, the retry prompt) are plain functions. The menus
+//! themselves are shown by the configured launcher (see `launcher`), rofi unless set otherwise.
+
+use crate::{
+    launcher::{self, Pick},
+    style::Style,
+};
+use anyhow::Result;
+use std::{fmt::Display, sync::OnceLock};
+
+/// The `tab-stops` for rows with tab-separated columns, from the style.
+static TAB_STOPS: OnceLock<Vec<String>> = OnceLock::new();
+
+pub fn init(style: &Style) {
+    if style.columns {
+        let _ = TAB_STOPS.set(style.tab_stops.clone());
+    }
+}
+
+/// One line of a menu. Besides the label and the value it stands for, a row can carry an icon
+/// (shown when rofi runs with `show-icons`), be highlighted as active or urgent, or be a
+/// non-selectable header. These are passed to rofi as dmenu row options.
+pub struct Row<T> {
+    pub label: String,
+    pub item: T,
+    pub icon: Option<String>,
+    pub active: bool,
+    pub urgent: bool,
+    pub selectable: bool,
+}
+
+impl<T> Row<T> {
+    pub fn new(label: String, item: T) -> Self {
+        Self {
+            label,
+            item,
+            icon: None,
+            active: false,
+            urgent: false,
+            selectable: true,
+        }
+    }
+
+    /// A section title that rofi skips over when moving the selection.
+    pub fn header(label: String, item: T) -> Self {
+        Self {
+            selectable: false,
+            ..Self::new(label, item)
+        }
+    }
+
+    pub fn icon(mut self, icon: &str) -> Self {
+        self.icon = Some(icon.to_string());
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    pub fn urgent(mut self, urgent: bool) -> Self {
+        self.urgent = urgent;
+        self
+    }
+
+    /// The line written to rofi: the label followed by any row options.
+    pub fn render(&self) -> String {
+        let mut options = vec![];
+        if let Some(icon) = &self.icon {
+            options.push(format!("icon\x1f{}", icon));
+        }
+        if self.active {
+            options.push("active\x1ftrue".to_string());
+        }
+        if self.urgent {
+            options.push("urgent\x1ftrue".to_string());
+        }
+        if !self.selectable {
+            options.push("nonselectable\x1ftrue".to_string());
+        }
+        if options.is_empty() {
+            self.label.clone()
+        } else {
+            format!("{}\0{}", self.label, options.join("\x1f"))
+        }
+    }
+}
+
+impl<T> From<T> for Row<T>
+where
+    T: Display,
+{
+    fn from(item: T) -> Self {
+        Self::new(item.to_string(), item)
+    }
+}
+
+impl<T> Display for Row<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+pub fn rich_rofi<T, U>(prompt: &str, items: Vec<T>) -> Result<U, rofi::Error>
+where
+    T: Into<Row<U>>,
+{
+    pick(prompt, items, false)
+}
+
+/// Like `rich_rofi`, optionally treating the labels as pango markup.
+pub fn pick<T, U>(prompt: &str, items: Vec<T>, markup: bool) -> Result<U, rofi::Error>
+where
+    T: Into<Row<U>>,
+{
+    pick_keyed(prompt, items, markup, &[]).map(|(item, _)| item)
+}
+
+/// Like `pick`, with rofi's custom keybindings given as `(N, key)` for `-kb-custom-N`. The number
+/// of the custom key the row was chosen with, if any, comes back with it.
+pub fn pick_keyed<T, U>(
+    prompt: &str,
+    items: Vec<T>,
+    markup: bool,
+    keys: &[(u8, String)],
+) -> Result<(U, Option<u8>), rofi::Error>
+where
+    T: Into<Row<U>>,
+{
+    pick_with_message(prompt, items, markup, keys, None, 0)
+}
+
+/// Like `pick_keyed`, with a `message` (pango markup) in rofi's message pane and the row at
+/// `selected` highlighted to start with.
+pub fn pick_with_message<T, U>(
+    prompt: &str,
+    items: Vec<T>,
+    markup: bool,
+    keys: &[(u8, String)],
+    message: Option<&str>,
+    selected: usize,
+) -> Result<(U, Option<u8>), rofi::Error>
+where
+    T: Into<Row<U>>,
+{
+    let mut items: Vec<Row<U>> = items.into_iter().map(|i| i.into()).collect();
+    let labels: Vec<_> = items.iter().map(Row::render).collect();
+    let tab_stops = TAB_STOPS.get().filter(|stops| !stops.is_empty());
+    let mut selected = selected;
+    loop {
+        let (idx, key) = launcher::current().pick(&Pick {
+            prompt,
+            labels: &labels,
+            markup,
+            keys,
+            message,
+            selected,
+            tab_stops: tab_stops.map(Vec::as_slice),
+        })?;
+        // Only rofi keeps headers from being picked; elsewhere picking one asks again
+        if items[idx].selectable {
+            // use `swap_remove` so we don't have to re-order the list we're about the throw away anyways
+            return Ok((items.swap_remove(idx).item, key));
+        }
+        selected = idx;
+    }
+}
+
+/// A single row of text, like an error.
+pub fn message(text: &str) -> Result<(), rofi::Error> {
+    pick("", vec![Row::new(text.to_string(), ())], false)
+}
+
+/// Free text with no suggestions.
+pub fn text(prompt: &str) -> Result<String, rofi::Error> {
+    text_with(prompt, &[])
+}
+
+/// Free text, with rows (presets, history) that can be picked instead of typing.
+pub fn text_with(prompt: &str, suggestions: &[String]) -> Result<String, rofi::Error> {
+    launcher::current().text(prompt, suggestions)
+}
+
+/// Free text that must parse, asking again with the problem in the prompt until it does.
+pub fn validated<T, F>(prompt: &str, suggestions: &[String], mut parse: F) -> Result<T>
+where
+    F: FnMut(&str) -> Result<T>,
+{
+    let mut current_prompt = prompt.to_string();
+    loop {
+        let input = text_with(&current_prompt, suggestions)?;
+        match parse(&input) {
+            Ok(value) => return Ok(value),
+            Err(err) => current_prompt = retry_prompt(&err, prompt),
+        }
+    }
+}
+
+pub fn retry_prompt(err: &dyn Display, prompt: &str) -> String {
+    format!("{} - {}", err, prompt)
+}
+
+/// A yes/no question, with optional lines of preview below the two answers. Picking a preview
+/// line (possible with rofi versions that ignore `nonselectable`) asks again.
+pub fn confirm(
+    prompt: &str,
+    yes: &str,
+    no: &str,
+    preview: Vec<String>,
+) -> Result<bool, rofi::Error> {
+    let mut rows = vec![
+        Row::new(yes.to_string(), Some(true)),
+        Row::new(no.to_string(), Some(false)),
+    ];
+    rows.extend(
+        preview
+            .iter()
+            .map(|line| Row::header(format!("    {}", line), None)),
+    );
+
+    match rich_rofi(prompt, rows)? {
+        Some(answer) => Ok(answer),
+        None => confirm(prompt, yes, no, preview),
+    }
+}
+
+/// Lets the user tick any number of items, returning the ticked ones in their original order.
+pub fn multi_select<T>(prompt: &str, items: Vec<Row<T>>) -> Result<Vec<T>, rofi::Error> {
+    let mut selected = vec![false; items.len()];
+    loop {
+        let labels = multi_select_labels(&items, &selected);
+        let rows = labels
+            .into_iter()
+            .enumerate()
+            .map(|(idx, label)| Row::new(label, idx));
+        match pick(prompt, rows.collect(), false)? {
+            0 => break,
+            1 => selected.iter_mut().for_each(|s| *s = true),
+            2 => selected.iter_mut().for_each(|s| *s = false),
+            idx => selected[idx - 3] = !selected[idx - 3],
+        }
+    }
+
+    Ok(items
+        .into_iter()
+        .zip(selected)
+        .filter(|(_, selected)| *selected)
+        .map(|(item, _)| item.item)
+        .collect())
+}
+
+/// The apply/all/none rows followed by one checkbox row per item.
+pub fn multi_select_labels<T>(items: &[Row<T>], selected: &[bool]) -> Vec<String> {
+    let count = selected.iter().filter(|s| **s).count();
+    let mut labels = vec![
+        tr!("Apply to {} selected", count),
+        tr!("Select all"),
+        tr!("Select none"),
+    ];
+    labels.extend(
+        items.iter().zip(selected).map(|(item, selected)| {
+            format!("[{}] {}", if *selected { "x" } else { " " }, item.label)
+        }),
+    );
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_row_is_just_the_label() {
+        assert_eq!(Row::new("Add".to_string(), ()).render(), "Add");
+    }
+
+    #[test]
+    fn row_options_follow_a_nul() {
+        let row = Row::new("Call mom".to_string(), ())
+            .icon("phone")
+            .active(true)
+            .urgent(true);
+        assert_eq!(
+            row.render(),
+            "Call mom\0icon\x1fphone\x1factive\x1ftrue\x1furgent\x1ftrue"
+        );
+    }
+
+    #[test]
+    fn header_is_nonselectable() {
+        assert_eq!(
+            Row::header("Today".to_string(), ()).render(),
+            "Today\0nonselectable\x1ftrue"
+        );
+    }
+
+    #[test]
+    fn retry_prompt_puts_the_problem_first() {
+        assert_eq!(
+            retry_prompt(&"`x` is not a duration", "Estimate"),
+            "`x` is not a duration - Estimate"
+        );
+    }
+}
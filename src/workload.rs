@@ -0,0 +1,124 @@
+//! A histogram of what is due on each of the next two weeks' days, by task count or by summed
+//! estimates, to spot overloaded days before adding to them. Days are grouped into this week,
+//! next week and later.
+//!
+//! Days over `[scheduler] daily_capacity` hours are highlighted. Tasks without an estimate count
+//! as `default_estimate` hours.
+
+use crate::{
+    back_on_escape, planner, query_sorted, quick,
+    style::Glyph,
+    weeks,
+    widgets::{rich_rofi, Row},
+    App,
+};
+use anyhow::Result;
+use chrono::{offset::Local as LocalTime, Duration, NaiveDate, TimeZone};
+use task_hookrs::task::Task;
+
+const DAYS: i64 = 14;
+const BAR_WIDTH: f64 = 20.0;
+
+enum Pick {
+    Day(NaiveDate),
+    Header,
+    Toggle,
+}
+
+pub fn workload(app: &App) -> Result<()> {
+    let config = &app.config.scheduler;
+    let today = LocalTime::now().naive_local().date();
+    let mut hours = false;
+    loop {
+        let tasks = query_sorted(&format!(
+            "status:pending due.after:{} due.before:{}",
+            (today - Duration::days(1)).format("%Y-%m-%d"),
+            (today + Duration::days(DAYS)).format("%Y-%m-%d")
+        ))?;
+        let days: Vec<_> = (0..DAYS)
+            .map(|offset| {
+                let day = today + Duration::days(offset);
+                let due: Vec<_> = tasks
+                    .iter()
+                    .filter(|task| due_on(task) == Some(day))
+                    .collect();
+                let booked: f64 = due
+                    .iter()
+                    .map(|task| planner::estimate_hours(task).unwrap_or(config.default_estimate))
+                    .sum();
+                (day, due.len(), booked)
+            })
+            .collect();
+
+        let value = |count: usize, booked: f64| if hours { booked } else { count as f64 };
+        let max = days
+            .iter()
+            .map(|(_, count, booked)| value(*count, *booked))
+            .fold(0.0, f64::max);
+        let mut rows = vec![];
+        let mut bucket = None;
+        for (day, count, booked) in &days {
+            let day_bucket = weeks::bucket(*day, today, weeks::first_day());
+            if bucket.as_ref() != Some(&day_bucket) {
+                rows.push(Row::header(day_bucket.label(), Pick::Header));
+                bucket = Some(day_bucket);
+            }
+            let filled = if max > 0.0 {
+                (value(*count, *booked) / max * BAR_WIDTH).round() as usize
+            } else {
+                0
+            };
+            let amount = if hours {
+                format!("{:.1}h", booked)
+            } else {
+                count.to_string()
+            };
+            rows.push(
+                Row::new(
+                    format!(
+                        "{}  {}{}  {}",
+                        day.format("%a %m-%d"),
+                        app.config.style.glyph(Glyph::BarFull).repeat(filled),
+                        app.config
+                            .style
+                            .glyph(Glyph::BarEmpty)
+                            .repeat(BAR_WIDTH as usize - filled),
+                        amount
+                    ),
+                    Pick::Day(*day),
+                )
+                .urgent(*booked > config.daily_capacity),
+            );
+        }
+        rows.push(Row::new(
+            if hours {
+                tr!("Show task counts")
+            } else {
+                tr!("Show estimated hours")
+            },
+            Pick::Toggle,
+        ));
+
+        match rich_rofi(&tr!("Workload"), rows)? {
+            Pick::Toggle => hours = !hours,
+            Pick::Header => {}
+            Pick::Day(day) => {
+                let due: Vec<_> = tasks
+                    .iter()
+                    .filter(|task| due_on(task) == Some(day))
+                    .cloned()
+                    .collect();
+                if !due.is_empty() {
+                    let prompt = day.format("%a %Y-%m-%d").to_string();
+                    back_on_escape(quick::pick_and_act(&prompt, due, &app.config.style))?;
+                }
+            }
+        }
+    }
+}
+
+/// The local day a task is due on; taskwarrior exports dates in UTC.
+fn due_on(task: &Task) -> Option<NaiveDate> {
+    task.due()
+        .map(|due| LocalTime.from_utc_datetime(due).naive_local().date())
+}